@@ -1,4 +1,4 @@
-use bininfo::Result;
+use bininfo::{BinExInfo, Result};
 use prettytable::{color, format::Alignment, Attr, Cell, Row, Table};
 
 fn main() -> Result<()> {
@@ -16,7 +16,12 @@ fn main() -> Result<()> {
         );
         return Ok(());
     }
+    print_tables(res);
 
+    Ok(())
+}
+
+fn print_tables(res: BinExInfo) {
     // ================= Entry point table =================
     let mut tbl = Table::new();
     tbl.set_titles(Row::new(vec![Cell::new_align(
@@ -82,12 +87,410 @@ fn main() -> Result<()> {
                 ),
             ]));
         }
+        if let Some(offset) = entry_point.offset {
+            tbl.add_row(Row::new(vec![
+                Cell::new("File Offset"),
+                Cell::new(&format!("{:#01x}", offset)),
+            ]));
+        }
+        if !entry_point.bytes.is_empty() {
+            let bytes = entry_point
+                .bytes
+                .chunks(16)
+                .map(|line| {
+                    line.iter()
+                        .map(|b| format!("{:02X}", b))
+                        .collect::<Vec<_>>()
+                        .join(" ")
+                })
+                .collect::<Vec<_>>()
+                .join("\n");
+            tbl.add_row(Row::new(vec![Cell::new("Bytes"), Cell::new(&bytes)]));
+        }
+        if !entry_point.signatures.is_empty() {
+            let signatures = entry_point
+                .signatures
+                .iter()
+                .map(|m| m.name.clone())
+                .collect::<Vec<_>>()
+                .join("\n");
+            tbl.add_row(Row::new(vec![
+                Cell::new("Signatures"),
+                Cell::new(&signatures).with_style(Attr::Bold),
+            ]));
+        }
     } else {
         no_data_available(&mut tbl);
     }
     // Print the Entrypoint table
     tbl.printstd();
 
+    // ================= ELF Header table =================
+    let mut tbl = Table::new();
+    tbl.set_titles(Row::new(vec![Cell::new_align(
+        "ELF Header",
+        Alignment::CENTER,
+    )
+    .with_hspan(2)
+    .with_style(Attr::Bold)
+    .with_style(Attr::ForegroundColor(color::BRIGHT_BLUE))]));
+    if let Some(header) = res.elf_header {
+        let rows = [
+            ("Class", format!("{:?}", header.ident)),
+            ("Endianness", format!("{:?}", header.endianness)),
+            ("Type", header.r#type.clone()),
+            (
+                "Machine",
+                format!("{} ({})", header.machine, header.machine_id),
+            ),
+            (
+                "OS/ABI",
+                format!("{} (version {})", header.os_abi, header.abi_version),
+            ),
+            (
+                "Flags",
+                if header.flags_description.is_empty() {
+                    format!("{:#01x}", header.flags)
+                } else {
+                    format!(
+                        "{:#01x} ({})",
+                        header.flags,
+                        header.flags_description.join(", ")
+                    )
+                },
+            ),
+            ("Entry point", format!("{:#01x}", header.entry_point)),
+        ];
+        for (name, value) in rows {
+            tbl.add_row(Row::new(vec![Cell::new(name), Cell::new(&value)]));
+        }
+    } else {
+        no_data_available(&mut tbl);
+    }
+    // Print the ELF Header table
+    tbl.printstd();
+
+    // ================= Toolchain table =================
+    let mut tbl = Table::new();
+    tbl.set_titles(Row::new(vec![Cell::new_align(
+        "Toolchain",
+        Alignment::CENTER,
+    )
+    .with_hspan(2)
+    .with_style(Attr::Bold)
+    .with_style(Attr::ForegroundColor(color::BRIGHT_BLUE))]));
+    if let Some(toolchain) = res.toolchain {
+        let rows = [
+            ("Summary", toolchain.summary.clone()),
+            ("Compilers", toolchain.compilers.join(", ")),
+            ("Linker", toolchain.linker.unwrap_or_default()),
+            ("Languages", toolchain.languages.join(", ")),
+            ("Go version", toolchain.go_version.unwrap_or_default()),
+            ("Rust version", toolchain.rust_version.unwrap_or_default()),
+            ("rustc commit", toolchain.rustc_commit.unwrap_or_default()),
+            ("libc", toolchain.libc.unwrap_or_default()),
+            ("Comments", toolchain.comments.join("\n")),
+        ];
+        for (name, value) in rows.into_iter().filter(|(_, value)| !value.is_empty()) {
+            tbl.add_row(Row::new(vec![Cell::new(name), Cell::new(&value)]));
+        }
+    } else {
+        no_data_available(&mut tbl);
+    }
+    // Print the Toolchain table
+    tbl.printstd();
+
+    // ================= Go table =================
+    let mut tbl = Table::new();
+    tbl.set_titles(Row::new(vec![Cell::new_align("Go", Alignment::CENTER)
+        .with_hspan(2)
+        .with_style(Attr::Bold)
+        .with_style(Attr::ForegroundColor(color::BRIGHT_BLUE))]));
+    if let Some(go) = res.go {
+        tbl.add_row(Row::new(vec![Cell::new("Version"), Cell::new(&go.version)]));
+        if let Some(path) = &go.path {
+            tbl.add_row(Row::new(vec![Cell::new("Path"), Cell::new(path)]));
+        }
+        if let Some(module) = &go.main_module {
+            tbl.add_row(Row::new(vec![
+                Cell::new("Main module"),
+                Cell::new(&format!("{} {}", module.path, module.version)),
+            ]));
+        }
+        for setting in go.settings.iter() {
+            tbl.add_row(Row::new(vec![
+                Cell::new(&setting.key),
+                Cell::new(&setting.value),
+            ]));
+        }
+        tbl.add_row(Row::new(vec![
+            Cell::new("Dependency").with_style(Attr::Bold),
+            Cell::new("Version").with_style(Attr::Bold),
+        ]));
+        for module in go.dependencies.iter() {
+            let version = match &module.replace {
+                Some(replace) => {
+                    format!("{} => {} {}", module.version, replace.path, replace.version)
+                }
+                None => module.version.clone(),
+            };
+            tbl.add_row(Row::new(vec![Cell::new(&module.path), Cell::new(&version)]));
+        }
+        tbl.add_row(Row::new(vec![
+            Cell::new("Functions").with_style(Attr::Bold),
+            Cell::new(&format!(
+                "{} (pclntab {})",
+                go.functions.len(),
+                go.pclntab_version.unwrap_or_default()
+            )),
+        ]));
+    } else {
+        no_data_available(&mut tbl);
+    }
+    // Print the Go table
+    tbl.printstd();
+
+    // ================= Rust table =================
+    let mut tbl = Table::new();
+    tbl.set_titles(Row::new(vec![Cell::new_align("Rust", Alignment::CENTER)
+        .with_hspan(3)
+        .with_style(Attr::Bold)
+        .with_style(Attr::ForegroundColor(color::BRIGHT_BLUE))]));
+    if let Some(rust) = res.rust {
+        for (name, value) in [
+            ("rustc version", rust.rustc_version),
+            ("rustc commit", rust.rustc_commit),
+        ] {
+            if let Some(value) = value {
+                tbl.add_row(Row::new(vec![
+                    Cell::new(name),
+                    Cell::new(&value).with_hspan(2),
+                ]));
+            }
+        }
+        tbl.add_row(Row::new(vec![
+            Cell::new("Crate").with_style(Attr::Bold),
+            Cell::new("Version").with_style(Attr::Bold),
+            Cell::new("Source").with_style(Attr::Bold),
+        ]));
+        match rust.auditable {
+            // the dependency list is complete, the source paths only cover crates that can panic
+            Some(packages) => {
+                for v in packages.iter() {
+                    let source = match &v.kind {
+                        Some(kind) => format!("{} ({})", v.source, kind),
+                        None => v.source.clone(),
+                    };
+                    tbl.add_row(Row::new(vec![
+                        Cell::new(&v.name),
+                        Cell::new(&v.version),
+                        Cell::new(&source),
+                    ]));
+                }
+            }
+            None => {
+                for v in rust.crates.iter() {
+                    tbl.add_row(Row::new(vec![
+                        Cell::new(&v.name),
+                        Cell::new(&v.version),
+                        Cell::new("registry path"),
+                    ]));
+                }
+            }
+        }
+    } else {
+        no_data_available(&mut tbl);
+    }
+    // Print the Rust table
+    tbl.printstd();
+
+    // ================= Packers table =================
+    let mut tbl = Table::new();
+    tbl.set_titles(Row::new(vec![Cell::new_align(
+        "Packers",
+        Alignment::CENTER,
+    )
+    .with_hspan(4)
+    .with_style(Attr::Bold)
+    .with_style(Attr::ForegroundColor(color::BRIGHT_BLUE))]));
+    if let Some(packers) = res.packers {
+        tbl.add_row(Row::new(vec![
+            Cell::new("Packed"),
+            Cell::new(&packers.packed.to_string()).with_hspan(3),
+        ]));
+        if let Some(overlay) = packers.overlay {
+            tbl.add_row(Row::new(vec![
+                Cell::new("Overlay"),
+                Cell::new(&format!("{:#x} ({} bytes)", overlay.offset, overlay.size)).with_hspan(3),
+            ]));
+        }
+        for heuristic in packers.heuristics.iter() {
+            tbl.add_row(Row::new(vec![
+                Cell::new("Heuristic"),
+                Cell::new(heuristic).with_hspan(3),
+            ]));
+        }
+        if !packers.detections.is_empty() {
+            tbl.add_row(Row::new(vec![
+                Cell::new("Name").with_style(Attr::Bold),
+                Cell::new("Category").with_style(Attr::Bold),
+                Cell::new("Confidence").with_style(Attr::Bold),
+                Cell::new("Evidence").with_style(Attr::Bold),
+            ]));
+        }
+        for v in packers.detections.iter() {
+            tbl.add_row(Row::new(vec![
+                Cell::new(&v.name),
+                Cell::new(&v.category),
+                Cell::new(&format!("{}%", v.confidence)),
+                Cell::new(&v.evidence.join("\n")),
+            ]));
+        }
+    } else {
+        no_data_available(&mut tbl);
+    }
+    // Print the Packers table
+    tbl.printstd();
+
+    // ================= Installer table =================
+    let mut tbl = Table::new();
+    tbl.set_titles(Row::new(vec![Cell::new_align(
+        "Installer",
+        Alignment::CENTER,
+    )
+    .with_hspan(4)
+    .with_style(Attr::Bold)
+    .with_style(Attr::ForegroundColor(color::BRIGHT_BLUE))]));
+    if let Some(installer) = res.installer {
+        tbl.add_row(Row::new(vec![
+            Cell::new("Kind"),
+            Cell::new(&installer.kind).with_hspan(3),
+        ]));
+        if let Some(version) = installer.version {
+            tbl.add_row(Row::new(vec![
+                Cell::new("Version"),
+                Cell::new(&version).with_hspan(3),
+            ]));
+        }
+        let location = match installer.offset {
+            Some(offset) => format!("{} at {:#x}", installer.source, offset),
+            None => installer.source,
+        };
+        tbl.add_row(Row::new(vec![
+            Cell::new("Location"),
+            Cell::new(&location).with_hspan(3),
+        ]));
+        if let Some(compression) = installer.compression {
+            tbl.add_row(Row::new(vec![
+                Cell::new("Compression"),
+                Cell::new(&compression).with_hspan(3),
+            ]));
+        }
+        for detail in installer.details.iter() {
+            tbl.add_row(Row::new(vec![
+                Cell::new("Detail"),
+                Cell::new(detail).with_hspan(3),
+            ]));
+        }
+        if installer.encrypted {
+            tbl.add_row(Row::new(vec![
+                Cell::new("Encrypted"),
+                Cell::new("true").with_hspan(3),
+            ]));
+        }
+        if !installer.members.is_empty() {
+            tbl.add_row(Row::new(vec![
+                Cell::new("Name").with_style(Attr::Bold),
+                Cell::new("Size").with_style(Attr::Bold),
+                Cell::new("Packed Size").with_style(Attr::Bold),
+                Cell::new("Compression").with_style(Attr::Bold),
+            ]));
+        }
+        for v in installer.members.iter() {
+            tbl.add_row(Row::new(vec![
+                Cell::new(&v.name),
+                Cell::new(&v.size.to_string()),
+                Cell::new(&v.packed_size.map(|s| s.to_string()).unwrap_or_default()),
+                Cell::new(&v.compression),
+            ]));
+        }
+    } else {
+        no_data_available(&mut tbl);
+    }
+    // Print the Installer table
+    tbl.printstd();
+
+    // ================= Python table =================
+    let mut tbl = Table::new();
+    tbl.set_titles(Row::new(vec![Cell::new_align("Python", Alignment::CENTER)
+        .with_hspan(6)
+        .with_style(Attr::Bold)
+        .with_style(Attr::ForegroundColor(color::BRIGHT_BLUE))]));
+    if let Some(python) = res.python {
+        tbl.add_row(Row::new(vec![
+            Cell::new("Packager"),
+            Cell::new(&python.packager).with_hspan(5),
+        ]));
+        if let Some(version) = python.python_version {
+            tbl.add_row(Row::new(vec![
+                Cell::new("Python Version"),
+                Cell::new(&version).with_hspan(5),
+            ]));
+        }
+        if let Some(library) = python.python_library {
+            tbl.add_row(Row::new(vec![
+                Cell::new("Python Library"),
+                Cell::new(&library).with_hspan(5),
+            ]));
+        }
+        if let Some(offset) = python.offset {
+            tbl.add_row(Row::new(vec![
+                Cell::new("Archive Offset"),
+                Cell::new(&format!("{:#x}", offset)).with_hspan(5),
+            ]));
+        }
+        for script in python.entry_scripts.iter() {
+            tbl.add_row(Row::new(vec![
+                Cell::new("Entry Script"),
+                Cell::new(script).with_hspan(5),
+            ]));
+        }
+        for detail in python.details.iter() {
+            tbl.add_row(Row::new(vec![
+                Cell::new("Detail"),
+                Cell::new(detail).with_hspan(5),
+            ]));
+        }
+        if !python.members.is_empty() {
+            tbl.add_row(Row::new(vec![
+                Cell::new("Name").with_style(Attr::Bold),
+                Cell::new("Kind").with_style(Attr::Bold),
+                Cell::new("Offset").with_style(Attr::Bold),
+                Cell::new("Size").with_style(Attr::Bold),
+                Cell::new("Uncompressed Size").with_style(Attr::Bold),
+                Cell::new("Compressed").with_style(Attr::Bold),
+            ]));
+        }
+        for v in python.members.iter() {
+            tbl.add_row(Row::new(vec![
+                Cell::new(&v.name),
+                Cell::new(&v.kind),
+                Cell::new(&v.offset.map(|o| format!("{:#x}", o)).unwrap_or_default()),
+                Cell::new(&v.size.to_string()),
+                Cell::new(
+                    &v.uncompressed_size
+                        .map(|s| s.to_string())
+                        .unwrap_or_default(),
+                ),
+                Cell::new(&v.compressed.to_string()),
+            ]));
+        }
+    } else {
+        no_data_available(&mut tbl);
+    }
+    // Print the Python table
+    tbl.printstd();
+
     // ================= Sections table =================
     let mut tbl = Table::new();
     tbl.set_titles(Row::new(vec![Cell::new_align(
@@ -128,13 +531,102 @@ fn main() -> Result<()> {
     // Print the Sections table
     tbl.printstd();
 
+    // ================= Segments table =================
+    let mut tbl = Table::new();
+    tbl.set_titles(Row::new(vec![Cell::new_align(
+        "Segments",
+        Alignment::CENTER,
+    )
+    .with_hspan(9)
+    .with_style(Attr::Bold)
+    .with_style(Attr::ForegroundColor(color::BRIGHT_BLUE))]));
+    tbl.add_row(Row::new(vec![
+        Cell::new("Type").with_style(Attr::Bold),
+        Cell::new("Flags").with_style(Attr::Bold),
+        Cell::new("Offset").with_style(Attr::Bold),
+        Cell::new("Virtual Address").with_style(Attr::Bold),
+        Cell::new("File Size").with_style(Attr::Bold),
+        Cell::new("Memory Size").with_style(Attr::Bold),
+        Cell::new("Align").with_style(Attr::Bold),
+        Cell::new("Entropy").with_style(Attr::Bold),
+        Cell::new("Sections").with_style(Attr::Bold),
+    ]));
+    if let Some(segments) = res.segments {
+        if !segments.segments.is_empty() {
+            for v in segments.segments.iter() {
+                tbl.add_row(Row::new(vec![
+                    Cell::new(&v.r#type),
+                    Cell::new(&v.flags),
+                    Cell::new(&format!("{:#01x}", v.offset)),
+                    Cell::new(&format!("{:#01x}", v.virt_addr)),
+                    Cell::new(&format!("{:#01x}", v.file_size)),
+                    Cell::new(&format!("{:#01x}", v.mem_size)),
+                    Cell::new(&format!("{:#01x}", v.align)),
+                    Cell::new(&v.entropy.unwrap_or(0.0).to_string()),
+                    Cell::new(&v.sections.join(" ")),
+                ]));
+            }
+        } else {
+            no_data_available(&mut tbl);
+        }
+    } else {
+        no_data_available(&mut tbl);
+    }
+    // Print the Segments table
+    tbl.printstd();
+
+    // ================= Dynamic Section =================
+    let mut tbl = Table::new();
+    tbl.set_titles(Row::new(vec![Cell::new_align(
+        "Dynamic Section",
+        Alignment::CENTER,
+    )
+    .with_hspan(2)
+    .with_style(Attr::Bold)
+    .with_style(Attr::ForegroundColor(color::BRIGHT_BLUE))]));
+    if let Some(dynamic) = res.dynamic {
+        let paths = |paths: &[bininfo::elf::dynamic::SearchPath]| {
+            paths
+                .iter()
+                .map(|p| match (p.origin, p.world_writable) {
+                    (true, _) => format!("{} ($ORIGIN)", p.path),
+                    (_, true) => format!("{} (world-writable)", p.path),
+                    _ => p.path.clone(),
+                })
+                .collect::<Vec<_>>()
+                .join("\n")
+        };
+        let rows = [
+            ("Interpreter", dynamic.interpreter.unwrap_or_default()),
+            ("Needed", dynamic.needed.join("\n")),
+            ("SONAME", dynamic.soname.unwrap_or_default()),
+            ("RPATH", paths(&dynamic.rpath)),
+            ("RUNPATH", paths(&dynamic.runpath)),
+            ("Flags", dynamic.flags.join(" ")),
+            ("Flags_1", dynamic.flags_1.join(" ")),
+            ("BIND_NOW", dynamic.bind_now.to_string()),
+            ("PIE", dynamic.pie.to_string()),
+            ("NODELETE", dynamic.nodelete.to_string()),
+        ];
+        for (name, value) in rows {
+            tbl.add_row(Row::new(vec![
+                Cell::new(name).with_style(Attr::Bold),
+                Cell::new(&value),
+            ]));
+        }
+    } else {
+        no_data_available(&mut tbl);
+    }
+    // Print the Dynamic Section table
+    tbl.printstd();
+
     // ================= Rich Headers =================
     let mut tbl = Table::new();
     tbl.set_titles(Row::new(vec![Cell::new_align(
         "Rich Headers",
         Alignment::CENTER,
     )
-    .with_hspan(5)
+    .with_hspan(6)
     .with_style(Attr::Bold)
     .with_style(Attr::ForegroundColor(color::BRIGHT_BLUE))]));
     tbl.add_row(Row::new(vec![
@@ -143,6 +635,7 @@ fn main() -> Result<()> {
         Cell::new("Product ID").with_style(Attr::Bold),
         Cell::new("Count").with_style(Attr::Bold),
         Cell::new("Guessed Visual Studio Version").with_style(Attr::Bold),
+        Cell::new("Object Kind").with_style(Attr::Bold),
     ]));
     if let Some(rich_table) = res.rich_headers {
         if !rich_table.rich_entries.is_empty() {
@@ -153,8 +646,40 @@ fn main() -> Result<()> {
                     Cell::new(&v.product_id.to_string()),
                     Cell::new(&v.count.to_string()),
                     Cell::new(&v.guessed_visual_studio_version),
+                    Cell::new(&format!("{:?}", v.object_kind)),
                 ]));
             }
+            tbl.add_row(Row::new(vec![
+                Cell::new(&rich_table.toolchain.summary).with_hspan(6)
+            ]));
+            tbl.add_row(Row::new(vec![Cell::new(&format!(
+                "Offset: {:#01x}  Size: {:#01x}  Key: {:#010x}  Checksum: {:#010x} ({})",
+                rich_table.offset,
+                rich_table.size,
+                rich_table.key,
+                rich_table.checksum,
+                if rich_table.checksum_valid {
+                    "valid"
+                } else {
+                    "invalid"
+                }
+            ))
+            .with_hspan(6)]));
+            let spoof_cell = Cell::new(&format!(
+                "Spoof likelihood: {:?}",
+                rich_table.spoof_check.likelihood
+            ))
+            .with_hspan(6);
+            if rich_table.spoof_check.findings.is_empty() {
+                tbl.add_row(Row::new(vec![spoof_cell]));
+            } else {
+                tbl.add_row(Row::new(vec![spoof_cell
+                    .with_style(Attr::ForegroundColor(color::RED))
+                    .with_style(Attr::Bold)]));
+                for finding in rich_table.spoof_check.findings.iter() {
+                    tbl.add_row(Row::new(vec![Cell::new(finding).with_hspan(6)]));
+                }
+            }
         } else {
             no_data_available(&mut tbl);
         }
@@ -250,7 +775,10 @@ fn main() -> Result<()> {
                     .imports
                     .clone()
                     .into_iter()
-                    .map(|x| x.name)
+                    .map(|x| match x.version {
+                        Some(version) => format!("{}@{}", x.name, version),
+                        None => x.name,
+                    })
                     .collect::<Vec<_>>();
                 tbl.add_row(Row::new(vec![
                     Cell::new(&nv.name),
@@ -267,6 +795,266 @@ fn main() -> Result<()> {
     // Print the Imports table
     tbl.printstd();
 
+    // ================= Hardening table =================
+    let mut tbl = Table::new();
+    tbl.set_titles(Row::new(vec![Cell::new_align(
+        "Hardening",
+        Alignment::CENTER,
+    )
+    .with_hspan(2)
+    .with_style(Attr::Bold)
+    .with_style(Attr::ForegroundColor(color::BRIGHT_BLUE))]));
+    if let Some(hardening) = res.hardening {
+        let rows = [
+            ("PIE", format!("{:?}", hardening.pie)),
+            ("NX", hardening.nx.to_string()),
+            ("RELRO", format!("{:?}", hardening.relro)),
+            ("Canary", hardening.canary.to_string()),
+            ("FORTIFY_SOURCE", hardening.fortified.join(" ")),
+            ("RPATH", hardening.rpath.to_string()),
+            ("RUNPATH", hardening.runpath.to_string()),
+            (
+                "CET IBT/SHSTK",
+                format!("{}/{}", hardening.ibt, hardening.shstk),
+            ),
+            ("BTI/PAC", format!("{}/{}", hardening.bti, hardening.pac)),
+            ("Stripped", hardening.stripped.to_string()),
+        ];
+        for (name, value) in rows {
+            tbl.add_row(Row::new(vec![
+                Cell::new(name).with_style(Attr::Bold),
+                Cell::new(&value),
+            ]));
+        }
+    } else {
+        no_data_available(&mut tbl);
+    }
+    // Print the Hardening table
+    tbl.printstd();
+
+    // ================= Notes table =================
+    let mut tbl = Table::new();
+    tbl.set_titles(Row::new(vec![Cell::new_align("Notes", Alignment::CENTER)
+        .with_hspan(4)
+        .with_style(Attr::Bold)
+        .with_style(Attr::ForegroundColor(color::BRIGHT_BLUE))]));
+    tbl.add_row(Row::new(vec![
+        Cell::new("Owner").with_style(Attr::Bold),
+        Cell::new("Type").with_style(Attr::Bold),
+        Cell::new("Section").with_style(Attr::Bold),
+        Cell::new("Value").with_style(Attr::Bold),
+    ]));
+    if let Some(notes) = res.notes {
+        if !notes.notes.is_empty() {
+            for v in notes.notes.iter() {
+                tbl.add_row(Row::new(vec![
+                    Cell::new(&v.owner),
+                    Cell::new(&v.type_name),
+                    Cell::new(&v.section.clone().unwrap_or_default()),
+                    Cell::new(&format!("{:?}", v.value)),
+                ]));
+            }
+        } else {
+            no_data_available(&mut tbl);
+        }
+    } else {
+        no_data_available(&mut tbl);
+    }
+    // Print the Notes table
+    tbl.printstd();
+
+    // ================= GOT Slots table =================
+    let mut tbl = Table::new();
+    tbl.set_titles(Row::new(vec![Cell::new_align(
+        "GOT Slots",
+        Alignment::CENTER,
+    )
+    .with_hspan(3)
+    .with_style(Attr::Bold)
+    .with_style(Attr::ForegroundColor(color::BRIGHT_BLUE))]));
+    tbl.add_row(Row::new(vec![
+        Cell::new("Symbol").with_style(Attr::Bold),
+        Cell::new("Address").with_style(Attr::Bold),
+        Cell::new("PLT").with_style(Attr::Bold),
+    ]));
+    if let Some(relocations) = res.relocations {
+        if !relocations.got_slots.is_empty() {
+            for v in relocations.got_slots.iter() {
+                tbl.add_row(Row::new(vec![
+                    Cell::new(&v.symbol),
+                    Cell::new(&format!("{:#01x}", v.address)),
+                    Cell::new(&v.plt.to_string()),
+                ]));
+            }
+        } else {
+            no_data_available(&mut tbl);
+        }
+        tbl.add_row(Row::new(vec![
+            Cell::new("Relocations").with_style(Attr::Bold),
+            Cell::new(&relocations.relocations.len().to_string()).with_hspan(2),
+        ]));
+    } else {
+        no_data_available(&mut tbl);
+    }
+    // Print the GOT Slots table
+    tbl.printstd();
+
+    // ================= Exports table =================
+    let mut tbl = Table::new();
+    tbl.set_titles(Row::new(vec![Cell::new_align(
+        "Exports",
+        Alignment::CENTER,
+    )
+    .with_hspan(6)
+    .with_style(Attr::Bold)
+    .with_style(Attr::ForegroundColor(color::BRIGHT_BLUE))]));
+    tbl.add_row(Row::new(vec![
+        Cell::new("Name").with_style(Attr::Bold),
+        Cell::new("Version").with_style(Attr::Bold),
+        Cell::new("Type").with_style(Attr::Bold),
+        Cell::new("Binding").with_style(Attr::Bold),
+        Cell::new("Value").with_style(Attr::Bold),
+        Cell::new("Section").with_style(Attr::Bold),
+    ]));
+    if let Some(exports) = res.exports {
+        if !exports.symbols.is_empty() {
+            for v in exports.symbols.iter() {
+                tbl.add_row(Row::new(vec![
+                    Cell::new(v.demangled.as_ref().unwrap_or(&v.name)),
+                    Cell::new(&v.version.clone().unwrap_or_default()),
+                    Cell::new(&v.r#type),
+                    Cell::new(&v.binding),
+                    Cell::new(&format!("{:#01x}", v.value)),
+                    Cell::new(&v.section.clone().unwrap_or_default()),
+                ]));
+            }
+        } else {
+            no_data_available(&mut tbl);
+        }
+    } else {
+        no_data_available(&mut tbl);
+    }
+    if let Some(symbols) = res.symbols {
+        tbl.add_row(Row::new(vec![
+            Cell::new("Static symbols").with_style(Attr::Bold),
+            Cell::new(&symbols.symbols.len().to_string()).with_hspan(5),
+        ]));
+    }
+    // Print the Exports table
+    tbl.printstd();
+
+    // ================= Symbol Versions table =================
+    let mut tbl = Table::new();
+    tbl.set_titles(Row::new(vec![Cell::new_align(
+        "Symbol Versions",
+        Alignment::CENTER,
+    )
+    .with_hspan(3)
+    .with_style(Attr::Bold)
+    .with_style(Attr::ForegroundColor(color::BRIGHT_BLUE))]));
+    tbl.add_row(Row::new(vec![
+        Cell::new("Library").with_style(Attr::Bold),
+        Cell::new("Index").with_style(Attr::Bold),
+        Cell::new("Version").with_style(Attr::Bold),
+    ]));
+    if let Some(versions) = res.versions {
+        if versions.definitions.is_empty() && versions.requirements.is_empty() {
+            no_data_available(&mut tbl);
+        }
+        for v in versions.definitions.iter() {
+            tbl.add_row(Row::new(vec![
+                Cell::new("<defined>"),
+                Cell::new(&v.index.to_string()),
+                Cell::new(&v.name),
+            ]));
+        }
+        for lib in versions.requirements.iter() {
+            for v in lib.versions.iter() {
+                tbl.add_row(Row::new(vec![
+                    Cell::new(&lib.library),
+                    Cell::new(&v.index.to_string()),
+                    Cell::new(&v.name),
+                ]));
+            }
+        }
+        for (name, version) in [
+            ("Minimum glibc", versions.glibc),
+            ("Minimum GLIBCXX", versions.glibcxx),
+            ("Minimum CXXABI", versions.cxxabi),
+        ] {
+            if let Some(version) = version {
+                tbl.add_row(Row::new(vec![
+                    Cell::new(name).with_style(Attr::Bold),
+                    Cell::new(&version).with_hspan(2),
+                ]));
+            }
+        }
+    } else {
+        no_data_available(&mut tbl);
+    }
+    // Print the Symbol Versions table
+    tbl.printstd();
+
+    // ================= Debug Info table =================
+    let mut tbl = Table::new();
+    tbl.set_titles(Row::new(vec![Cell::new_align(
+        "Debug Info",
+        Alignment::CENTER,
+    )
+    .with_hspan(4)
+    .with_style(Attr::Bold)
+    .with_style(Attr::ForegroundColor(color::BRIGHT_BLUE))]));
+    if let Some(debug_info) = res.debug_info {
+        let sections: Vec<String> = debug_info
+            .sections
+            .iter()
+            .map(|s| {
+                if s.compressed {
+                    format!("{} (compressed)", s.name)
+                } else {
+                    s.name.clone()
+                }
+            })
+            .collect();
+        let mut rows = vec![("Sections", sections.join(", "))];
+        if let Some(link) = &debug_info.debuglink {
+            rows.push((
+                "Debug link",
+                format!("{} (crc {:#010x})", link.file, link.crc),
+            ));
+        }
+        if let Some(link) = &debug_info.debugaltlink {
+            rows.push((
+                "Debug alt link",
+                format!("{} ({})", link.file, link.build_id),
+            ));
+        }
+        for (name, value) in rows {
+            tbl.add_row(Row::new(vec![
+                Cell::new(name).with_style(Attr::Bold),
+                Cell::new(&value).with_hspan(3),
+            ]));
+        }
+        tbl.add_row(Row::new(vec![
+            Cell::new("Compile Unit").with_style(Attr::Bold),
+            Cell::new("Language").with_style(Attr::Bold),
+            Cell::new("Producer").with_style(Attr::Bold),
+            Cell::new("Files").with_style(Attr::Bold),
+        ]));
+        for v in debug_info.compile_units.iter() {
+            tbl.add_row(Row::new(vec![
+                Cell::new(&v.name.clone().unwrap_or_default()),
+                Cell::new(&v.language.clone().unwrap_or_default()),
+                Cell::new(&v.producer.clone().unwrap_or_default()),
+                Cell::new(&v.files.len().to_string()),
+            ]));
+        }
+    } else {
+        no_data_available(&mut tbl);
+    }
+    // Print the Debug Info table
+    tbl.printstd();
+
     // ================= Resources table =================
     let mut tbl = Table::new();
     tbl.set_titles(Row::new(vec![Cell::new_align(
@@ -343,7 +1131,92 @@ fn main() -> Result<()> {
     // Print the Thread Local Storage (TLS) Callback table
     tbl.printstd();
 
-    Ok(())
+    // ================= Init Functions table =================
+    let mut tbl = Table::new();
+    tbl.set_titles(Row::new(vec![Cell::new_align(
+        "Init/Fini Functions",
+        Alignment::CENTER,
+    )
+    .with_hspan(4)
+    .with_style(Attr::Bold)
+    .with_style(Attr::ForegroundColor(color::BRIGHT_BLUE))]));
+    if let Some(init_functions) = res.init_functions {
+        if !init_functions.functions.is_empty() {
+            for v in init_functions.functions.iter() {
+                tbl.add_row(Row::new(vec![
+                    Cell::new(&format!("{:?}", v.kind)),
+                    Cell::new(&format!("{:#01x}", v.address)),
+                    Cell::new(&v.section.clone().unwrap_or_default()),
+                    Cell::new(&v.symbol.clone().unwrap_or_default()),
+                ]));
+            }
+        } else {
+            no_data_available(&mut tbl);
+        }
+    } else {
+        no_data_available(&mut tbl);
+    }
+    // Print the Init Functions table
+    tbl.printstd();
+
+    // ================= Mitigations table =================
+    let mut tbl = Table::new();
+    tbl.set_titles(Row::new(vec![Cell::new_align(
+        "Mitigations",
+        Alignment::CENTER,
+    )
+    .with_hspan(3)
+    .with_style(Attr::Bold)
+    .with_style(Attr::ForegroundColor(color::BRIGHT_BLUE))]));
+    if let Some(mitigations) = res.mitigations {
+        if !mitigations.checks.is_empty() {
+            for v in mitigations.checks.iter() {
+                let color = match v.status {
+                    bininfo::pe::mitigations::CheckStatus::Pass => color::GREEN,
+                    bininfo::pe::mitigations::CheckStatus::Fail => color::RED,
+                    bininfo::pe::mitigations::CheckStatus::NotApplicable => color::BLUE,
+                };
+                tbl.add_row(Row::new(vec![
+                    Cell::new(&v.name).with_style(Attr::Bold),
+                    Cell::new(&format!("{:?}", v.status)).with_style(Attr::ForegroundColor(color)),
+                    Cell::new(&v.reason),
+                ]));
+            }
+        } else {
+            no_data_available(&mut tbl);
+        }
+    } else {
+        no_data_available(&mut tbl);
+    }
+    // Print the Mitigations table
+    tbl.printstd();
+
+    // ================= UPX table =================
+    if let Some(upx) = res.upx {
+        let mut tbl = Table::new();
+        tbl.set_titles(Row::new(vec![Cell::new_align("UPX", Alignment::CENTER)
+            .with_hspan(2)
+            .with_style(Attr::Bold)
+            .with_style(Attr::ForegroundColor(color::BRIGHT_BLUE))]));
+        let rows = [
+            ("Method", upx.method.clone()),
+            ("Filter", format!("{:#04x}", upx.filter)),
+            ("Format", upx.format.to_string()),
+            ("Compressed size", upx.compressed_size.to_string()),
+            ("Unpacked size", upx.size.to_string()),
+        ];
+        for (name, value) in rows {
+            tbl.add_row(Row::new(vec![Cell::new(name), Cell::new(&value)]));
+        }
+        // Print the UPX table
+        tbl.printstd();
+    }
+
+    // Print the unpacked binary
+    if let Some(unpacked) = res.unpacked {
+        println!("\n================= Unpacked =================\n");
+        print_tables(*unpacked);
+    }
 }
 
 fn no_data_available(tbl: &mut Table) {
@@ -0,0 +1,74 @@
+use crate::error::Error;
+use std::{
+    path::Path,
+    sync::{RwLock, RwLockReadGuard},
+};
+
+/// Version reported for matches from a database compiled into the crate.
+pub const BUILTIN_DATABASE: &str = "builtin";
+
+/// A signature database that can be loaded at runtime on top of a built-in one.
+///
+/// Every implementor keeps its loaded databases in a `RwLock` of its own, later databases
+/// take precedence over earlier ones.
+pub trait SignatureDatabase: Sized + 'static {
+    /// Text of the database compiled into the crate.
+    const BUILTIN: &'static str;
+    /// Character starting a comment line of the text form.
+    const COMMENT: char;
+
+    /// Parses a database from its text form, malformed entries are skipped.
+    fn parse(version: &str, data: &str) -> Self;
+
+    /// The loaded databases.
+    fn databases() -> &'static RwLock<Vec<Self>>;
+
+    /// Parses the database compiled into the crate.
+    fn builtin() -> Self {
+        Self::parse(BUILTIN_DATABASE, Self::BUILTIN)
+    }
+
+    /// Reads a database from a file.
+    ///
+    /// The version is taken from a `version: <version>` comment when the file has one,
+    /// from the file name otherwise.
+    fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        // e.g. PEiD databases are not always UTF-8
+        let data = String::from_utf8_lossy(&std::fs::read(&path)?).to_string();
+        let version = data
+            .lines()
+            .filter_map(|line| line.strip_prefix(Self::COMMENT))
+            .filter_map(|comment| comment.trim().split_once(':'))
+            .find(|(key, _)| key.trim().eq_ignore_ascii_case("version"))
+            .map(|(_, version)| version.trim().to_string())
+            .unwrap_or_else(|| path.as_ref().display().to_string());
+        Ok(Self::parse(&version, &data))
+    }
+
+    /// Merges the database over the ones already loaded, its entries take precedence.
+    fn merge(self) {
+        Self::databases()
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .push(self);
+    }
+
+    /// Replaces every loaded database, including the built-in one.
+    fn replace(self) {
+        *Self::databases()
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()) = vec![self];
+    }
+
+    /// Restores the built-in database as the only one loaded.
+    fn reset() {
+        Self::builtin().replace();
+    }
+
+    /// Locks the loaded databases for reading.
+    fn loaded() -> RwLockReadGuard<'static, Vec<Self>> {
+        Self::databases()
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
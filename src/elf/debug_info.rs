@@ -0,0 +1,214 @@
+use crate::util::{c_string, read_u32, safe_read};
+use flate2::read::ZlibDecoder;
+use gimli::{
+    AttributeValue, DW_AT_comp_dir, DW_AT_language, DW_AT_name, DW_AT_producer, Dwarf,
+    DwarfSections, EndianSlice, Reader, RunTimeEndian, SectionId,
+};
+use goblin::elf::{
+    section_header::{SectionHeader, SHT_NOBITS},
+    Elf,
+};
+use serde::{Deserialize, Serialize};
+use std::{borrow::Cow, io::Read};
+
+const SHF_COMPRESSED: u64 = 0x800;
+const ELFCOMPRESS_ZLIB: u32 = 1;
+/// Upper bound for a decompressed section, guards against zip bombs
+const MAX_DECOMPRESSED_SIZE: u64 = 256 * 1024 * 1024;
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct DebugSection {
+    pub name: String,
+    pub size: u64,
+    /// `SHF_COMPRESSED` or a legacy `.zdebug_*` section
+    pub compressed: bool,
+}
+
+/// `.gnu_debuglink`, the separate debug file and the CRC32 of its contents.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct DebugLink {
+    pub file: String,
+    pub crc: u32,
+}
+
+/// `.gnu_debugaltlink`, the supplementary debug file shared with other binaries (dwz).
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct DebugAltLink {
+    pub file: String,
+    pub build_id: String,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct CompileUnit {
+    pub name: Option<String>,
+    /// Compiler and flags, e.g. `GNU C17 13.2.0 -mtune=generic -O2`
+    pub producer: Option<String>,
+    /// `DW_LANG_*` without the prefix, e.g. `C11` or `Rust`
+    pub language: Option<String>,
+    pub comp_dir: Option<String>,
+    pub dwarf_version: u16,
+    /// Source files of the line program
+    pub files: Vec<String>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct DebugInfo {
+    pub sections: Vec<DebugSection>,
+    pub debuglink: Option<DebugLink>,
+    pub debugaltlink: Option<DebugAltLink>,
+    pub compile_units: Vec<CompileUnit>,
+}
+
+impl TryFrom<(&Elf<'_>, &[u8])> for DebugInfo {
+    type Error = crate::error::Error;
+
+    fn try_from(elf: (&Elf, &[u8])) -> Result<Self, Self::Error> {
+        let (elf, data) = elf;
+        let name = |sh: &SectionHeader| elf.shdr_strtab.get_at(sh.sh_name).unwrap_or_default();
+        let content = |sh: &SectionHeader| {
+            if sh.sh_type == SHT_NOBITS {
+                &[][..]
+            } else {
+                safe_read(data, sh.sh_offset as usize, sh.sh_size as usize)
+            }
+        };
+        let mut result = DebugInfo::default();
+        for sh in elf.section_headers.iter() {
+            let name = name(sh);
+            if name.starts_with(".debug_") || name.starts_with(".zdebug_") {
+                result.sections.push(DebugSection {
+                    name: name.to_string(),
+                    size: sh.sh_size,
+                    compressed: sh.sh_flags & SHF_COMPRESSED != 0 || name.starts_with(".zdebug_"),
+                });
+            }
+            match name {
+                ".gnu_debuglink" => {
+                    let bytes = content(sh);
+                    let file = c_string(bytes);
+                    // the CRC follows the name, aligned to 4 bytes
+                    let crc_offset = (file.len() + 1).next_multiple_of(4);
+                    let crc = bytes
+                        .get(crc_offset..crc_offset + 4)
+                        .map(|b| {
+                            let b = b.try_into().unwrap_or_default();
+                            if elf.little_endian {
+                                u32::from_le_bytes(b)
+                            } else {
+                                u32::from_be_bytes(b)
+                            }
+                        })
+                        .unwrap_or_default();
+                    result.debuglink = Some(DebugLink { file, crc });
+                }
+                ".gnu_debugaltlink" => {
+                    let bytes = content(sh);
+                    let file = c_string(bytes);
+                    let build_id = hex::encode(bytes.get(file.len() + 1..).unwrap_or_default());
+                    result.debugaltlink = Some(DebugAltLink { file, build_id });
+                }
+                _ => {}
+            }
+        }
+
+        let load = |id: SectionId| -> gimli::Result<Cow<[u8]>> {
+            let zname = id.name().replacen(".debug_", ".zdebug_", 1);
+            Ok(elf
+                .section_headers
+                .iter()
+                .find(|sh| name(sh) == id.name() || name(sh) == zname)
+                .and_then(|sh| section_data(elf, sh, content(sh)))
+                .unwrap_or_default())
+        };
+        // malformed DWARF is reported as whatever could be read before the error
+        if let Ok(sections) = DwarfSections::load(load) {
+            let endian = if elf.little_endian {
+                RunTimeEndian::Little
+            } else {
+                RunTimeEndian::Big
+            };
+            let dwarf = sections.borrow(|section| EndianSlice::new(section, endian));
+            let _ = compile_units(&dwarf, &mut result.compile_units);
+        }
+        Ok(result)
+    }
+}
+
+/// Section contents, decompressed if needed.
+fn section_data<'a>(elf: &Elf, sh: &SectionHeader, bytes: &'a [u8]) -> Option<Cow<'a, [u8]>> {
+    if sh.sh_flags & SHF_COMPRESSED != 0 {
+        // Elf32_Chdr / Elf64_Chdr, only zlib is supported
+        let header_size = if elf.is_64 { 24 } else { 12 };
+        if read_u32(bytes, 0, elf.little_endian)? != ELFCOMPRESS_ZLIB {
+            return None;
+        }
+        decompress(bytes.get(header_size..)?).map(Cow::Owned)
+    } else if bytes.starts_with(b"ZLIB") {
+        // legacy .zdebug_*: "ZLIB" and the big endian uncompressed size
+        decompress(bytes.get(12..)?).map(Cow::Owned)
+    } else {
+        Some(Cow::Borrowed(bytes))
+    }
+}
+
+fn decompress(bytes: &[u8]) -> Option<Vec<u8>> {
+    let mut out = vec![];
+    ZlibDecoder::new(bytes)
+        .take(MAX_DECOMPRESSED_SIZE)
+        .read_to_end(&mut out)
+        .ok()?;
+    Some(out)
+}
+
+fn compile_units<R: Reader>(dwarf: &Dwarf<R>, units: &mut Vec<CompileUnit>) -> gimli::Result<()> {
+    let string = |unit: &gimli::Unit<R>, value: AttributeValue<R>| {
+        dwarf
+            .attr_string(unit, value)
+            .ok()
+            .and_then(|s| s.to_string_lossy().ok().map(|s| s.into_owned()))
+    };
+    let mut headers = dwarf.units();
+    while let Some(header) = headers.next()? {
+        let unit = dwarf.unit(header)?;
+        let mut entries = unit.entries();
+        let Some((_, root)) = entries.next_dfs()? else {
+            continue;
+        };
+        let attr = |name| root.attr_value(name).ok().flatten();
+        let mut cu = CompileUnit {
+            name: attr(DW_AT_name).and_then(|v| string(&unit, v)),
+            producer: attr(DW_AT_producer).and_then(|v| string(&unit, v)),
+            language: match attr(DW_AT_language) {
+                Some(AttributeValue::Language(lang)) => Some(
+                    lang.static_string()
+                        .map(|s| s.trim_start_matches("DW_LANG_").to_string())
+                        .unwrap_or_else(|| format!("{:#x}", lang.0)),
+                ),
+                _ => None,
+            },
+            comp_dir: attr(DW_AT_comp_dir).and_then(|v| string(&unit, v)),
+            dwarf_version: unit.header.version(),
+            files: vec![],
+        };
+        if let Some(program) = &unit.line_program {
+            let header = program.header();
+            for file in header.file_names() {
+                let Some(name) = string(&unit, file.path_name()) else {
+                    continue;
+                };
+                let dir = file.directory(header).and_then(|d| string(&unit, d));
+                let path = match dir {
+                    Some(dir) if !dir.is_empty() && !name.starts_with('/') => {
+                        format!("{dir}/{name}")
+                    }
+                    _ => name,
+                };
+                if !cu.files.contains(&path) {
+                    cu.files.push(path);
+                }
+            }
+        }
+        units.push(cu);
+    }
+    Ok(())
+}
@@ -0,0 +1,137 @@
+use goblin::elf::{
+    dynamic::{
+        df_1_tag_to_str, df_tag_to_str, DF_1_NODELETE, DF_1_NOW, DF_1_PIE, DF_BIND_NOW,
+        DT_BIND_NOW, DT_FINI, DT_FINI_ARRAY, DT_FINI_ARRAYSZ, DT_FLAGS, DT_FLAGS_1, DT_INIT,
+        DT_INIT_ARRAY, DT_INIT_ARRAYSZ, DT_PREINIT_ARRAY, DT_PREINIT_ARRAYSZ,
+    },
+    Elf,
+};
+use serde::{Deserialize, Serialize};
+
+/// Directories that any local user can usually write to.
+const WORLD_WRITABLE_DIRS: &[&str] = &["/tmp", "/var/tmp", "/dev/shm", "/var/crash"];
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct SearchPath {
+    pub path: String,
+    /// Path is relative to the object itself (`$ORIGIN`).
+    pub origin: bool,
+    /// Path is empty, relative to the working directory or in a world-writable
+    /// directory, so anyone may be able to plant a library there.
+    pub world_writable: bool,
+}
+
+impl From<&str> for SearchPath {
+    fn from(path: &str) -> Self {
+        let origin = path.starts_with("$ORIGIN") || path.starts_with("${ORIGIN}");
+        let world_writable = !origin
+            && (!path.starts_with('/')
+                || WORLD_WRITABLE_DIRS
+                    .iter()
+                    .any(|dir| path == *dir || path.starts_with(&format!("{dir}/"))));
+        SearchPath {
+            path: path.to_string(),
+            origin,
+            world_writable,
+        }
+    }
+}
+
+/// An address/size pair from the dynamic table, e.g. `DT_INIT_ARRAY` and `DT_INIT_ARRAYSZ`.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct DynamicArray {
+    pub virt_addr: u64,
+    pub size: u64,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct DynamicSection {
+    /// Program interpreter from `PT_INTERP`
+    pub interpreter: Option<String>,
+    /// Libraries from `DT_NEEDED`, in load order
+    pub needed: Vec<String>,
+    pub soname: Option<String>,
+    pub rpath: Vec<SearchPath>,
+    pub runpath: Vec<SearchPath>,
+    /// `DT_FLAGS` names
+    pub flags: Vec<String>,
+    /// `DT_FLAGS_1` names
+    pub flags_1: Vec<String>,
+    pub bind_now: bool,
+    pub pie: bool,
+    pub nodelete: bool,
+    pub init: Option<u64>,
+    pub fini: Option<u64>,
+    pub preinit_array: Option<DynamicArray>,
+    pub init_array: Option<DynamicArray>,
+    pub fini_array: Option<DynamicArray>,
+}
+
+impl TryFrom<&Elf<'_>> for DynamicSection {
+    type Error = crate::error::Error;
+
+    fn try_from(elf: &Elf) -> Result<Self, Self::Error> {
+        let mut result = DynamicSection {
+            interpreter: elf.interpreter.map(|i| i.to_string()),
+            needed: elf.libraries.iter().map(|l| l.to_string()).collect(),
+            soname: elf.soname.map(|s| s.to_string()),
+            rpath: search_paths(&elf.rpaths),
+            runpath: search_paths(&elf.runpaths),
+            ..Default::default()
+        };
+        let Some(dynamic) = &elf.dynamic else {
+            return Ok(result);
+        };
+        let (mut flags, mut flags_1) = (0, 0);
+        let (mut preinit_array, mut init_array, mut fini_array) = (None, None, None);
+        let (mut preinit_arraysz, mut init_arraysz, mut fini_arraysz) = (0, 0, 0);
+        for d in &dynamic.dyns {
+            match d.d_tag {
+                DT_FLAGS => flags = d.d_val,
+                DT_FLAGS_1 => flags_1 = d.d_val,
+                DT_BIND_NOW => result.bind_now = true,
+                DT_INIT => result.init = Some(d.d_val),
+                DT_FINI => result.fini = Some(d.d_val),
+                DT_PREINIT_ARRAY => preinit_array = Some(d.d_val),
+                DT_PREINIT_ARRAYSZ => preinit_arraysz = d.d_val,
+                DT_INIT_ARRAY => init_array = Some(d.d_val),
+                DT_INIT_ARRAYSZ => init_arraysz = d.d_val,
+                DT_FINI_ARRAY => fini_array = Some(d.d_val),
+                DT_FINI_ARRAYSZ => fini_arraysz = d.d_val,
+                _ => {}
+            }
+        }
+        let array = |virt_addr: Option<u64>, size| {
+            virt_addr.map(|virt_addr| DynamicArray { virt_addr, size })
+        };
+        result.preinit_array = array(preinit_array, preinit_arraysz);
+        result.init_array = array(init_array, init_arraysz);
+        result.fini_array = array(fini_array, fini_arraysz);
+        result.flags = flag_names(flags, df_tag_to_str);
+        result.flags_1 = flag_names(flags_1, df_1_tag_to_str);
+        result.bind_now |= flags & DF_BIND_NOW != 0 || flags_1 & DF_1_NOW != 0;
+        result.pie = flags_1 & DF_1_PIE != 0;
+        result.nodelete = flags_1 & DF_1_NODELETE != 0;
+        Ok(result)
+    }
+}
+
+/// Splits `DT_RPATH`/`DT_RUNPATH` strings on `:` into separate search paths.
+fn search_paths(paths: &[&str]) -> Vec<SearchPath> {
+    paths
+        .iter()
+        .flat_map(|p| p.split(':'))
+        .map(SearchPath::from)
+        .collect()
+}
+
+fn flag_names(flags: u64, to_str: fn(u64) -> &'static str) -> Vec<String> {
+    (0..64)
+        .map(|bit| 1u64 << bit)
+        .filter(|flag| flags & flag != 0)
+        .map(|flag| match to_str(flag) {
+            "UNKNOWN_TAG" => format!("{flag:#x}"),
+            name => name.to_string(),
+        })
+        .collect()
+}
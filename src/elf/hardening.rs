@@ -0,0 +1,125 @@
+use crate::elf::{notes::NoteValue, ElfFileInformation};
+use goblin::elf::header::ET_DYN;
+use serde::{Deserialize, Serialize};
+
+/// Imports that show the binary was built with a stack protector.
+const CANARY_SYMBOLS: &[&str] = &[
+    "__stack_chk_fail",
+    "__stack_chk_guard",
+    "__intel_security_cookie",
+];
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub enum Pie {
+    #[default]
+    No,
+    Pie,
+    /// Shared object, position independent but not an executable
+    Dso,
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub enum Relro {
+    #[default]
+    No,
+    Partial,
+    Full,
+}
+
+/// Hardening flags, the same checks as `checksec.sh`.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Hardening {
+    pub pie: Pie,
+    /// Non executable stack (`PT_GNU_STACK` without `X`)
+    pub nx: bool,
+    pub relro: Relro,
+    pub canary: bool,
+    pub fortify: bool,
+    /// Imported `*_chk` functions from `_FORTIFY_SOURCE`
+    pub fortified: Vec<String>,
+    pub rpath: bool,
+    pub runpath: bool,
+    /// x86 CET indirect branch tracking
+    pub ibt: bool,
+    /// x86 CET shadow stack
+    pub shstk: bool,
+    /// AArch64 branch target identification
+    pub bti: bool,
+    /// AArch64 pointer authentication
+    pub pac: bool,
+    /// No `.symtab`
+    pub stripped: bool,
+}
+
+impl From<&ElfFileInformation> for Hardening {
+    fn from(elf: &ElfFileInformation) -> Self {
+        let has_segment = |name: &str| elf.segments.segments.iter().any(|s| s.r#type == name);
+        let imports: Vec<&str> = elf
+            .imports
+            .modules
+            .iter()
+            .flat_map(|m| m.imports.iter().map(|f| f.name.as_str()))
+            .chain(
+                elf.symbols
+                    .iter()
+                    .flat_map(|t| t.symbols.iter().map(|s| s.name.as_str())),
+            )
+            .collect();
+        let mut fortified: Vec<String> = imports
+            .iter()
+            .map(|name| name.split('@').next().unwrap_or(name))
+            .filter(|name| {
+                name.starts_with("__") && name.ends_with("_chk") && !CANARY_SYMBOLS.contains(name)
+            })
+            .map(|name| name.to_string())
+            .collect();
+        fortified.sort();
+        fortified.dedup();
+        let features: Vec<&str> = elf
+            .notes
+            .notes
+            .iter()
+            .filter_map(|n| match &n.value {
+                NoteValue::Properties(props) => Some(props),
+                _ => None,
+            })
+            .flatten()
+            .filter(|p| p.name.ends_with("_FEATURE_1_AND"))
+            .flat_map(|p| p.features.iter().map(|f| f.as_str()))
+            .collect();
+
+        Hardening {
+            pie: if elf.header.type_id != ET_DYN {
+                Pie::No
+            } else if elf.dynamic.pie
+                || (elf.dynamic.interpreter.is_some() && elf.dynamic.soname.is_none())
+            {
+                Pie::Pie
+            } else {
+                Pie::Dso
+            },
+            nx: elf
+                .segments
+                .segments
+                .iter()
+                .any(|s| s.r#type == "PT_GNU_STACK" && !s.flags.contains('X')),
+            relro: match (has_segment("PT_GNU_RELRO"), elf.dynamic.bind_now) {
+                (false, _) => Relro::No,
+                (true, false) => Relro::Partial,
+                (true, true) => Relro::Full,
+            },
+            canary: imports
+                .iter()
+                .any(|name| CANARY_SYMBOLS.contains(&name.split('@').next().unwrap_or(name))),
+            fortify: !fortified.is_empty(),
+            fortified,
+            rpath: !elf.dynamic.rpath.is_empty(),
+            runpath: !elf.dynamic.runpath.is_empty(),
+            ibt: features.contains(&"IBT"),
+            shstk: features.contains(&"SHSTK"),
+            bti: features.contains(&"BTI"),
+            pac: features.contains(&"PAC"),
+            stripped: elf.symbols.is_none(),
+        }
+    }
+}
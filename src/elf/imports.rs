@@ -0,0 +1,71 @@
+use crate::pe::imports::{ImportEntry, ImportFunction, Imports};
+use goblin::elf::{
+    section_header::SHN_UNDEF,
+    sym::{self, STB_GLOBAL, STB_WEAK},
+    Elf,
+};
+use std::collections::HashMap;
+
+/// Module name for imported symbols without a version requirement, the providing
+/// library of those can only be known at load time.
+pub const UNVERSIONED_MODULE: &str = "<unversioned>";
+
+impl TryFrom<&Elf<'_>> for Imports {
+    type Error = crate::error::Error;
+
+    fn try_from(elf: &Elf) -> Result<Self, Self::Error> {
+        // version index -> (library, version) from .gnu.version_r
+        let mut requirements: HashMap<u16, (&str, &str)> = HashMap::new();
+        if let Some(verneed) = &elf.verneed {
+            for need in verneed.iter() {
+                let file = elf.dynstrtab.get_at(need.vn_file).unwrap_or_default();
+                for aux in need.iter() {
+                    let name = elf.dynstrtab.get_at(aux.vna_name).unwrap_or_default();
+                    requirements.insert(aux.vna_other, (file, name));
+                }
+            }
+        }
+
+        let mut modules: Vec<ImportEntry> = elf
+            .libraries
+            .iter()
+            .map(|lib| ImportEntry {
+                name: lib.to_string(),
+                imports: vec![],
+            })
+            .collect();
+        for (idx, sym) in elf.dynsyms.iter().enumerate() {
+            let bind = sym.st_bind();
+            if sym.st_shndx != SHN_UNDEF as usize || (bind != STB_GLOBAL && bind != STB_WEAK) {
+                continue;
+            }
+            let name = match elf.dynstrtab.get_at(sym.st_name) {
+                Some(name) if !name.is_empty() => name,
+                _ => continue,
+            };
+            let requirement = elf
+                .versym
+                .as_ref()
+                .and_then(|versym| versym.get_at(idx))
+                .and_then(|v| requirements.get(&v.version()));
+            let (module, version) = match requirement {
+                Some(&(file, version)) => (file, Some(version.to_string())),
+                None => (UNVERSIONED_MODULE, None),
+            };
+            let function = ImportFunction {
+                name: name.to_string(),
+                import_by_ordinal: false,
+                version,
+                symbol_type: Some(sym::type_to_str(sym.st_type()).to_string()),
+            };
+            match modules.iter_mut().find(|m| m.name == module) {
+                Some(entry) => entry.imports.push(function),
+                None => modules.push(ImportEntry {
+                    name: module.to_string(),
+                    imports: vec![function],
+                }),
+            }
+        }
+        Ok(Imports { modules })
+    }
+}
@@ -0,0 +1,137 @@
+use crate::{
+    elf::{
+        dynamic::{DynamicArray, DynamicSection},
+        relocations::{section_at, Relocations},
+        segments::vaddr_to_offset,
+    },
+    util::{read_uint, safe_read},
+};
+use goblin::elf::{
+    section_header::{SHT_FINI_ARRAY, SHT_INIT_ARRAY, SHT_PREINIT_ARRAY},
+    sym::STT_FUNC,
+    Elf,
+};
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub enum InitKind {
+    #[default]
+    Init,
+    Fini,
+    PreinitArray,
+    InitArray,
+    FiniArray,
+}
+
+/// Code the loader runs before the entry point or at exit, the ELF counterpart
+/// of TLS callbacks.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct InitFunction {
+    pub kind: InitKind,
+    pub address: u64,
+    pub section: Option<String>,
+    pub symbol: Option<String>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct InitFunctions {
+    pub functions: Vec<InitFunction>,
+}
+
+impl InitFunctions {
+    pub fn parse(
+        elf: (&Elf, &[u8]),
+        dynamic: &DynamicSection,
+        relocations: &Relocations,
+    ) -> InitFunctions {
+        let mut result = InitFunctions { functions: vec![] };
+        if let Some(init) = dynamic.init {
+            result.push(elf.0, InitKind::Init, init);
+        }
+        if let Some(fini) = dynamic.fini {
+            result.push(elf.0, InitKind::Fini, fini);
+        }
+        let arrays = [
+            (
+                InitKind::PreinitArray,
+                &dynamic.preinit_array,
+                SHT_PREINIT_ARRAY,
+            ),
+            (InitKind::InitArray, &dynamic.init_array, SHT_INIT_ARRAY),
+            (InitKind::FiniArray, &dynamic.fini_array, SHT_FINI_ARRAY),
+        ];
+        for (kind, array, sh_type) in arrays {
+            // static binaries have no dynamic section, fall back to the section headers
+            let array = array.clone().or_else(|| {
+                elf.0
+                    .section_headers
+                    .iter()
+                    .find(|sh| sh.sh_type == sh_type)
+                    .map(|sh| DynamicArray {
+                        virt_addr: sh.sh_addr,
+                        size: sh.sh_size,
+                    })
+            });
+            let Some(array) = array else {
+                continue;
+            };
+            let Some(offset) = vaddr_to_offset(elf.0, array.virt_addr) else {
+                continue;
+            };
+            let word = if elf.0.is_64 { 8 } else { 4 };
+            let entries = safe_read(elf.1, offset, array.size as usize).chunks_exact(word);
+            for (i, entry) in entries.enumerate() {
+                let slot = array.virt_addr + (i * word) as u64;
+                let address = match relocated(elf.0, relocations, slot) {
+                    Some(address) => address,
+                    None => read_uint(entry, 0, word, elf.0.little_endian).unwrap_or_default(),
+                };
+                // 0 and -1 are padding and sentinels
+                if address == 0 || address == u64::MAX >> (64 - word * 8) {
+                    continue;
+                }
+                result.push(elf.0, kind, address);
+            }
+        }
+        result
+    }
+
+    fn push(&mut self, elf: &Elf, kind: InitKind, address: u64) {
+        self.functions.push(InitFunction {
+            kind,
+            address,
+            section: section_at(elf, address),
+            symbol: symbol_at(elf, address),
+        });
+    }
+}
+
+/// Value a relocation stores in `slot`, for PIE binaries the array holds zeros in the file.
+fn relocated(elf: &Elf, relocations: &Relocations, slot: u64) -> Option<u64> {
+    let reloc = relocations
+        .relocations
+        .iter()
+        .find(|r| r.offset == slot && r.addend.is_some())?;
+    let addend = reloc.addend.unwrap_or_default() as u64;
+    match &reloc.symbol {
+        None => Some(addend),
+        Some(name) => elf
+            .dynsyms
+            .iter()
+            .find(|sym| sym.st_value != 0 && elf.dynstrtab.get_at(sym.st_name) == Some(name))
+            .map(|sym| sym.st_value.wrapping_add(addend)),
+    }
+}
+
+/// Name of the function at `address`, from `.symtab` or `.dynsym`.
+fn symbol_at(elf: &Elf, address: u64) -> Option<String> {
+    // ARM Thumb function pointers have the low bit set
+    let candidates = [address, address & !1];
+    elf.syms
+        .iter()
+        .map(|sym| (sym, &elf.strtab))
+        .chain(elf.dynsyms.iter().map(|sym| (sym, &elf.dynstrtab)))
+        .filter(|(sym, _)| sym.st_type() == STT_FUNC && candidates.contains(&sym.st_value))
+        .find_map(|(sym, strtab)| strtab.get_at(sym.st_name).filter(|n| !n.is_empty()))
+        .map(|name| name.to_string())
+}
@@ -1,15 +1,41 @@
 use crate::{
-    entry_point::EntryPoint,
-    pe::imports::{ImportEntry, ImportFunction, Imports},
-    sections::SectionTable,
-    Result,
+    entry_point::EntryPoint, go::GoInfo, installer::Installer, packers::Packers,
+    pe::imports::Imports, python::PythonInfo, rust::RustInfo, sections::SectionTable, Result,
 };
+use debug_info::DebugInfo;
+use dynamic::DynamicSection;
 use goblin::{
     container::{Container, Endian},
-    elf,
+    elf::{
+        self,
+        header::{
+            EI_ABIVERSION, EI_OSABI, EM_386, EM_68K, EM_AARCH64, EM_ARC_COMPACT, EM_ARM, EM_AVR,
+            EM_BPF, EM_IA_64, EM_MICROBLAZE, EM_MIPS, EM_MIPS_RS3_LE, EM_PARISC, EM_PPC, EM_PPC64,
+            EM_RISCV, EM_S390, EM_SH, EM_SPARC, EM_SPARC32PLUS, EM_SPARCV9, EM_X86_64, EM_XTENSA,
+        },
+    },
 };
+use hardening::Hardening;
+use init_functions::InitFunctions;
+use notes::Notes;
+use relocations::Relocations;
+use segments::SegmentTable;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use symbols::{Exports, SymbolTable};
+use toolchain::Toolchain;
+use versions::SymbolVersions;
+
+pub mod debug_info;
+pub mod dynamic;
+pub mod hardening;
+pub mod imports;
+pub mod init_functions;
+pub mod notes;
+pub mod relocations;
+pub mod segments;
+pub mod symbols;
+pub mod toolchain;
+pub mod versions;
 
 #[derive(Clone, Debug, Default, Serialize, Deserialize)]
 pub struct ElfFileInformation {
@@ -17,60 +43,66 @@ pub struct ElfFileInformation {
     pub section_table: SectionTable,
     pub header: Header,
     pub imports: Imports,
+    pub segments: SegmentTable,
+    pub dynamic: DynamicSection,
+    pub exports: Exports,
+    pub symbols: Option<SymbolTable>,
+    pub notes: Notes,
+    pub hardening: Hardening,
+    pub relocations: Relocations,
+    pub init_functions: InitFunctions,
+    pub versions: SymbolVersions,
+    pub debug_info: DebugInfo,
+    pub go: Option<GoInfo>,
+    pub rust: Option<RustInfo>,
+    pub toolchain: Toolchain,
+    pub packers: Packers,
+    pub installer: Option<Installer>,
+    pub python: Option<PythonInfo>,
 }
 
 impl ElfFileInformation {
-    pub fn parse(elf_file: &elf::Elf) -> Result<ElfFileInformation> {
-        Ok(ElfFileInformation {
-            entry_point: EntryPoint::try_from(elf_file)?,
-            section_table: SectionTable::try_from(elf_file)?,
-            header: Header::try_from(elf_file)?,
-            imports: Imports::try_from(elf_file)?,
-        })
+    pub fn parse(elf: (&elf::Elf, &[u8])) -> Result<ElfFileInformation> {
+        let mut info = ElfFileInformation {
+            entry_point: EntryPoint::try_from(elf)?,
+            section_table: SectionTable::try_from(elf.0)?,
+            header: Header::try_from(elf.0)?,
+            imports: Imports::try_from(elf.0)?,
+            segments: SegmentTable::try_from(elf)?,
+            dynamic: DynamicSection::try_from(elf.0)?,
+            exports: Exports::try_from(elf.0)?,
+            symbols: SymbolTable::parse(elf.0),
+            notes: Notes::try_from(elf)?,
+            hardening: Hardening::default(),
+            relocations: Relocations::try_from(elf)?,
+            init_functions: InitFunctions::default(),
+            versions: SymbolVersions::try_from(elf.0)?,
+            debug_info: DebugInfo::try_from(elf)?,
+            go: GoInfo::from_elf(elf),
+            rust: RustInfo::from_elf(elf),
+            toolchain: Toolchain::default(),
+            packers: Packers::default(),
+            installer: None,
+            python: None,
+        };
+        info.hardening = Hardening::from(&info);
+        info.init_functions = InitFunctions::parse(elf, &info.dynamic, &info.relocations);
+        info.toolchain = Toolchain::parse(elf, &info);
+        info.packers = Packers::from_elf(elf, &info.segments);
+        info.installer = Installer::from_elf(elf.1, info.packers.overlay.as_ref());
+        info.python = PythonInfo::from_elf(elf.1, info.packers.overlay.as_ref());
+        Ok(info)
     }
 }
 
-impl TryFrom<&elf::Elf<'_>> for Imports {
-    type Error = crate::error::Error;
-    fn try_from(elf: &elf::Elf) -> std::result::Result<Self, Self::Error> {
-        let syms: HashMap<String, Vec<String>> =
-            elf.syms.iter().fold(HashMap::new(), |mut acc, sym| {
-                if sym.is_import() && sym.is_function() {
-                    if let Some(ee) = elf.strtab.get_at(sym.st_name) {
-                        if let Some((func, module)) = ee.split_once("@@") {
-                            let e = acc.entry(module.to_string()).or_default();
-                            e.push(func.to_string())
-                        }
-                    }
-                }
-                acc
-            });
-        Ok(Imports {
-            modules: syms
-                .into_iter()
-                .map(|(module, funcs)| ImportEntry {
-                    name: module,
-                    imports: funcs
-                        .into_iter()
-                        .map(|f| ImportFunction {
-                            name: f,
-                            import_by_ordinal: false,
-                        })
-                        .collect(),
-                })
-                .collect(),
-        })
-    }
-}
-
-#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
 pub enum Identifier {
     #[default]
     Elf32,
     Elf64,
 }
 
-#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
 pub enum Endianness {
     #[default]
     Little,
@@ -89,6 +121,30 @@ pub struct Header {
     pub section_headers_count: u16,
     pub section_headers_offset: u64,
     pub section_header_size: u16,
+    /// `e_machine`, e.g. `AMD x86-64` or `AArch64`
+    #[serde(default)]
+    pub machine: String,
+    #[serde(default)]
+    pub machine_id: u16,
+    /// `EI_OSABI`, e.g. `UNIX System V` or `Linux`
+    #[serde(default)]
+    pub os_abi: String,
+    #[serde(default)]
+    pub abi_version: u8,
+    #[serde(default)]
+    pub flags: u32,
+    /// `e_flags` decoded for ARM, MIPS, RISC-V and PowerPC64
+    #[serde(default)]
+    pub flags_description: Vec<String>,
+    #[serde(default)]
+    pub header_size: u16,
+    #[serde(default)]
+    pub section_header_string_index: u16,
+    #[serde(default)]
+    pub entry_point: u64,
+    /// `e_type`, e.g. `ET_DYN`
+    #[serde(default)]
+    pub type_id: u16,
 }
 
 impl TryFrom<&elf::Elf<'_>> for Header {
@@ -113,6 +169,160 @@ impl TryFrom<&elf::Elf<'_>> for Header {
             section_headers_count: elf.header.e_shnum,
             section_headers_offset: elf.header.e_shoff,
             section_header_size: elf.header.e_shentsize,
+            machine: machine_name(elf.header.e_machine),
+            machine_id: elf.header.e_machine,
+            os_abi: os_abi_name(elf.header.e_ident[EI_OSABI]),
+            abi_version: elf.header.e_ident[EI_ABIVERSION],
+            flags: elf.header.e_flags,
+            flags_description: describe_flags(elf.header.e_machine, elf.header.e_flags),
+            header_size: elf.header.e_ehsize,
+            section_header_string_index: elf.header.e_shstrndx,
+            entry_point: elf.header.e_entry,
+            type_id: elf.header.e_type,
         })
     }
 }
+
+const EM_LOONGARCH: u16 = 258;
+
+fn machine_name(machine: u16) -> String {
+    match machine {
+        EM_386 => "Intel 80386",
+        EM_X86_64 => "AMD x86-64",
+        EM_AARCH64 => "AArch64",
+        EM_ARM => "ARM",
+        EM_MIPS => "MIPS",
+        EM_MIPS_RS3_LE => "MIPS (little endian)",
+        EM_PPC => "PowerPC",
+        EM_PPC64 => "PowerPC64",
+        EM_S390 => "IBM S/390",
+        EM_SPARC => "SPARC",
+        EM_SPARC32PLUS => "SPARC32+",
+        EM_SPARCV9 => "SPARC v9",
+        EM_SH => "Renesas SuperH",
+        EM_IA_64 => "Intel IA-64",
+        EM_68K => "Motorola 68000",
+        EM_PARISC => "HP PA-RISC",
+        EM_RISCV => "RISC-V",
+        EM_LOONGARCH => "LoongArch",
+        EM_ARC_COMPACT => "ARC",
+        EM_XTENSA => "Tensilica Xtensa",
+        EM_MICROBLAZE => "Xilinx MicroBlaze",
+        EM_AVR => "Atmel AVR",
+        EM_BPF => "Linux BPF",
+        _ => return elf::header::machine_to_str(machine).to_string(),
+    }
+    .to_string()
+}
+
+fn os_abi_name(os_abi: u8) -> String {
+    match os_abi {
+        0 => "UNIX System V",
+        1 => "HP-UX",
+        2 => "NetBSD",
+        3 => "Linux",
+        4 => "GNU Hurd",
+        6 => "Solaris",
+        7 => "AIX",
+        8 => "IRIX",
+        9 => "FreeBSD",
+        10 => "Tru64",
+        11 => "Novell Modesto",
+        12 => "OpenBSD",
+        13 => "OpenVMS",
+        14 => "NonStop Kernel",
+        15 => "AROS",
+        16 => "FenixOS",
+        17 => "CloudABI",
+        18 => "OpenVOS",
+        64 => "ARM EABI",
+        97 => "ARM",
+        255 => "Standalone",
+        _ => return format!("Unknown({os_abi})"),
+    }
+    .to_string()
+}
+
+/// Decodes the architecture specific `e_flags`.
+fn describe_flags(machine: u16, flags: u32) -> Vec<String> {
+    let mut result = vec![];
+    match machine {
+        EM_ARM => {
+            match flags >> 24 {
+                0 => result.push("GNU EABI (legacy)".to_string()),
+                version => result.push(format!("EABI{version}")),
+            }
+            if flags & 0x400 != 0 {
+                result.push("hard-float ABI".to_string());
+            }
+            if flags & 0x200 != 0 {
+                result.push("soft-float ABI".to_string());
+            }
+            if flags & 0x0080_0000 != 0 {
+                result.push("BE8".to_string());
+            }
+        }
+        EM_MIPS | EM_MIPS_RS3_LE => {
+            let arch = match flags >> 28 {
+                0 => "mips1",
+                1 => "mips2",
+                2 => "mips3",
+                3 => "mips4",
+                4 => "mips5",
+                5 => "mips32",
+                6 => "mips64",
+                7 => "mips32r2",
+                8 => "mips64r2",
+                9 => "mips32r6",
+                10 => "mips64r6",
+                _ => "unknown arch",
+            };
+            result.push(arch.to_string());
+            let abi = match flags & 0xf000 {
+                0x1000 => "O32",
+                0x2000 => "O64",
+                0x3000 => "EABI32",
+                0x4000 => "EABI64",
+                _ if flags & 0x20 != 0 => "N32",
+                _ if flags >> 28 == 6 || flags >> 28 == 8 || flags >> 28 == 10 => "N64",
+                _ => "O32",
+            };
+            result.push(abi.to_string());
+            for (flag, name) in [
+                (0x1, "noreorder"),
+                (0x2, "pic"),
+                (0x4, "cpic"),
+                (0x400, "nan2008"),
+            ] {
+                if flags & flag != 0 {
+                    result.push(name.to_string());
+                }
+            }
+        }
+        EM_RISCV => {
+            if flags & 0x1 != 0 {
+                result.push("RVC".to_string());
+            }
+            let float_abi = match flags & 0x6 {
+                0x0 => "soft-float ABI",
+                0x2 => "single-float ABI",
+                0x4 => "double-float ABI",
+                _ => "quad-float ABI",
+            };
+            result.push(float_abi.to_string());
+            if flags & 0x8 != 0 {
+                result.push("RVE".to_string());
+            }
+            if flags & 0x10 != 0 {
+                result.push("TSO".to_string());
+            }
+        }
+        EM_PPC64 => match flags & 0x3 {
+            1 => result.push("ELFv1 ABI".to_string()),
+            2 => result.push("ELFv2 ABI".to_string()),
+            _ => {}
+        },
+        _ => {}
+    }
+    result
+}
@@ -0,0 +1,300 @@
+use crate::util::{c_string, read_u32};
+use goblin::elf::{
+    header::{EM_386, EM_AARCH64, EM_X86_64},
+    note::{
+        NT_GNU_ABI_TAG, NT_GNU_BUILD_ID, NT_GNU_GOLD_VERSION, NT_GNU_HWCAP, NT_GNU_PROPERTY_TYPE_0,
+    },
+    section_header::SHT_NOTE,
+    Elf,
+};
+use serde::{Deserialize, Serialize};
+
+const NT_FDO_PACKAGING_METADATA: u32 = 0xcafe_1a7e;
+const NT_GO_BUILD_ID: u32 = 4;
+const NT_ANDROID_TYPE_IDENT: u32 = 1;
+const NT_FREEBSD_ABI_TAG: u32 = 1;
+const NT_FREEBSD_ARCH_TAG: u32 = 3;
+const NT_FREEBSD_FEATURE_CTL: u32 = 4;
+
+const GNU_PROPERTY_STACK_SIZE: u32 = 1;
+const GNU_PROPERTY_NO_COPY_ON_PROTECTED: u32 = 2;
+const GNU_PROPERTY_1_NEEDED: u32 = 0xb000_8000;
+const GNU_PROPERTY_X86_FEATURE_1_AND: u32 = 0xc000_0002;
+const GNU_PROPERTY_X86_FEATURE_2_NEEDED: u32 = 0xc000_8001;
+const GNU_PROPERTY_X86_ISA_1_NEEDED: u32 = 0xc000_8002;
+const GNU_PROPERTY_X86_FEATURE_2_USED: u32 = 0xc001_0001;
+const GNU_PROPERTY_X86_ISA_1_USED: u32 = 0xc001_0002;
+const GNU_PROPERTY_AARCH64_FEATURE_1_AND: u32 = 0xc000_0000;
+
+const X86_FEATURE_1: &[&str] = &["IBT", "SHSTK", "LAM_U48", "LAM_U57"];
+const X86_FEATURE_2: &[&str] = &[
+    "x86", "x87", "MMX", "XMM", "YMM", "ZMM", "FXSR", "XSAVE", "XSAVEOPT", "XSAVEC", "TMM", "MASK",
+];
+const X86_ISA_1: &[&str] = &["x86-64-baseline", "x86-64-v2", "x86-64-v3", "x86-64-v4"];
+const AARCH64_FEATURE_1: &[&str] = &["BTI", "PAC", "GCS"];
+const PROPERTY_1_NEEDED: &[&str] = &["INDIRECT_EXTERN_ACCESS"];
+const FREEBSD_FEATURE_CTL: &[&str] = &[
+    "ASLR_DISABLE",
+    "PROTMAX_DISABLE",
+    "STKGAP_DISABLE",
+    "WXNEEDED",
+    "LA48",
+    "ASG_DISABLE",
+];
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct GnuProperty {
+    pub r#type: u32,
+    pub name: String,
+    /// Names of the bits set in feature and ISA properties
+    pub features: Vec<String>,
+    /// Raw value of the property, if it fits in 64 bits
+    pub value: Option<u64>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum NoteValue {
+    /// `NT_GNU_BUILD_ID` as hex
+    BuildId(String),
+    /// `NT_GNU_ABI_TAG`, the OS and the minimum kernel version
+    AbiTag {
+        os: String,
+        kernel: String,
+    },
+    /// `NT_GNU_GOLD_VERSION`
+    GoldVersion(String),
+    /// `NT_GNU_PROPERTY_TYPE_0`
+    Properties(Vec<GnuProperty>),
+    /// `.note.package` JSON metadata
+    Package(String),
+    /// `.note.go.buildid`
+    GoBuildId(String),
+    /// `.note.android.ident`
+    Android {
+        api_level: u32,
+        ndk_version: Option<String>,
+        ndk_build: Option<String>,
+    },
+    /// `.note.tag` FreeBSD ABI tag (`__FreeBSD_version`)
+    FreeBsdAbiTag(u32),
+    FreeBsdArch(String),
+    FreeBsdFeatureControl(Vec<String>),
+    /// Description of notes we don't decode, as hex
+    Raw(String),
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Note {
+    pub owner: String,
+    pub r#type: u32,
+    pub type_name: String,
+    /// Section the note was read from, `None` when read from `PT_NOTE`
+    pub section: Option<String>,
+    pub value: NoteValue,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Notes {
+    pub notes: Vec<Note>,
+    /// GNU build-id as hex, used to match binaries to their debug symbols
+    pub build_id: Option<String>,
+}
+
+impl TryFrom<(&Elf<'_>, &[u8])> for Notes {
+    type Error = crate::error::Error;
+
+    fn try_from(elf: (&Elf, &[u8])) -> Result<Self, Self::Error> {
+        let mut result = Notes::default();
+        // iter_note_sections() walks every section of the given name, keep each name once
+        let mut section_names: Vec<&str> = vec![];
+        for sh in elf
+            .0
+            .section_headers
+            .iter()
+            .filter(|sh| sh.sh_type == SHT_NOTE)
+        {
+            if let Some(name) = elf.0.shdr_strtab.get_at(sh.sh_name) {
+                if !section_names.contains(&name) {
+                    section_names.push(name);
+                }
+            }
+        }
+        if section_names.is_empty() {
+            // no section headers, stripped or packed, fall back to the segments
+            if let Some(iter) = elf.0.iter_note_headers(elf.1) {
+                for note in iter.map_while(|n| n.ok()) {
+                    result.push(elf.0, None, note.name, note.n_type, note.desc);
+                }
+            }
+        }
+        for section in section_names {
+            if let Some(iter) = elf.0.iter_note_sections(elf.1, Some(section)) {
+                for note in iter.map_while(|n| n.ok()) {
+                    result.push(elf.0, Some(section), note.name, note.n_type, note.desc);
+                }
+            }
+        }
+        Ok(result)
+    }
+}
+
+impl Notes {
+    fn push(&mut self, elf: &Elf, section: Option<&str>, owner: &str, n_type: u32, desc: &[u8]) {
+        let owner = owner.trim_end_matches('\0');
+        let le = elf.little_endian;
+        let value = match (owner, n_type) {
+            ("GNU", NT_GNU_BUILD_ID) => NoteValue::BuildId(hex::encode(desc)),
+            ("GNU", NT_GNU_ABI_TAG) if desc.len() >= 16 => {
+                let os = match read_u32(desc, 0, le).unwrap_or_default() {
+                    0 => "Linux".to_string(),
+                    1 => "Hurd".to_string(),
+                    2 => "Solaris".to_string(),
+                    3 => "FreeBSD".to_string(),
+                    4 => "NetBSD".to_string(),
+                    5 => "Syllable".to_string(),
+                    6 => "NaCl".to_string(),
+                    os => format!("Unknown({os})"),
+                };
+                let kernel = format!(
+                    "{}.{}.{}",
+                    read_u32(desc, 4, le).unwrap_or_default(),
+                    read_u32(desc, 8, le).unwrap_or_default(),
+                    read_u32(desc, 12, le).unwrap_or_default()
+                );
+                NoteValue::AbiTag { os, kernel }
+            }
+            ("GNU", NT_GNU_GOLD_VERSION) => NoteValue::GoldVersion(c_string(desc)),
+            ("GNU", NT_GNU_PROPERTY_TYPE_0) => NoteValue::Properties(properties(elf, desc)),
+            ("FDO", NT_FDO_PACKAGING_METADATA) => NoteValue::Package(c_string(desc)),
+            ("Go", NT_GO_BUILD_ID) => NoteValue::GoBuildId(c_string(desc)),
+            ("Android", NT_ANDROID_TYPE_IDENT) if desc.len() >= 4 => {
+                let field = |off: usize| {
+                    desc.get(off..off + 64)
+                        .map(c_string)
+                        .filter(|s| !s.is_empty())
+                };
+                NoteValue::Android {
+                    api_level: read_u32(desc, 0, le).unwrap_or_default(),
+                    ndk_version: field(4),
+                    ndk_build: field(68),
+                }
+            }
+            ("FreeBSD", NT_FREEBSD_ABI_TAG) if desc.len() >= 4 => {
+                NoteValue::FreeBsdAbiTag(read_u32(desc, 0, le).unwrap_or_default())
+            }
+            ("FreeBSD", NT_FREEBSD_ARCH_TAG) => NoteValue::FreeBsdArch(c_string(desc)),
+            ("FreeBSD", NT_FREEBSD_FEATURE_CTL) if desc.len() >= 4 => {
+                NoteValue::FreeBsdFeatureControl(bit_names(
+                    read_u32(desc, 0, le).unwrap_or_default(),
+                    FREEBSD_FEATURE_CTL,
+                ))
+            }
+            _ => NoteValue::Raw(hex::encode(desc)),
+        };
+        if let NoteValue::BuildId(build_id) = &value {
+            self.build_id.get_or_insert_with(|| build_id.clone());
+        }
+        self.notes.push(Note {
+            owner: owner.to_string(),
+            r#type: n_type,
+            type_name: note_type_name(owner, n_type),
+            section: section.map(|s| s.to_string()),
+            value,
+        });
+    }
+}
+
+fn note_type_name(owner: &str, n_type: u32) -> String {
+    match (owner, n_type) {
+        ("GNU", NT_GNU_ABI_TAG) => "NT_GNU_ABI_TAG",
+        ("GNU", NT_GNU_HWCAP) => "NT_GNU_HWCAP",
+        ("GNU", NT_GNU_BUILD_ID) => "NT_GNU_BUILD_ID",
+        ("GNU", NT_GNU_GOLD_VERSION) => "NT_GNU_GOLD_VERSION",
+        ("GNU", NT_GNU_PROPERTY_TYPE_0) => "NT_GNU_PROPERTY_TYPE_0",
+        ("FDO", NT_FDO_PACKAGING_METADATA) => "NT_FDO_PACKAGING_METADATA",
+        ("Go", 1) => "NT_GO_PKGLIST",
+        ("Go", 2) => "NT_GO_ABIHASH",
+        ("Go", 3) => "NT_GO_DEPS",
+        ("Go", NT_GO_BUILD_ID) => "NT_GO_BUILDID",
+        ("Android", NT_ANDROID_TYPE_IDENT) => "NT_ANDROID_TYPE_IDENT",
+        ("Android", 3) => "NT_ANDROID_TYPE_KUSER",
+        ("Android", 4) => "NT_ANDROID_TYPE_MEMTAG",
+        ("FreeBSD", NT_FREEBSD_ABI_TAG) => "NT_FREEBSD_ABI_TAG",
+        ("FreeBSD", 2) => "NT_FREEBSD_NOINIT_TAG",
+        ("FreeBSD", NT_FREEBSD_ARCH_TAG) => "NT_FREEBSD_ARCH_TAG",
+        ("FreeBSD", NT_FREEBSD_FEATURE_CTL) => "NT_FREEBSD_FEATURE_CTL",
+        ("stapsdt", 3) => "NT_STAPSDT",
+        _ => return format!("{n_type:#x}"),
+    }
+    .to_string()
+}
+
+/// Decodes the `pr_type`/`pr_datasz`/`pr_data` array of a GNU property note.
+fn properties(elf: &Elf, desc: &[u8]) -> Vec<GnuProperty> {
+    let le = elf.little_endian;
+    let align = if elf.is_64 { 8 } else { 4 };
+    let machine = elf.header.e_machine;
+    let mut result = vec![];
+    let mut off = 0;
+    while off + 8 <= desc.len() {
+        let pr_type = read_u32(desc, off, le).unwrap_or_default();
+        let size = read_u32(desc, off + 4, le).unwrap_or_default() as usize;
+        let Some(data) = desc.get(off + 8..off + 8 + size) else {
+            break;
+        };
+        let value = match size {
+            4 => Some(read_u32(data, 0, le).unwrap_or_default() as u64),
+            8 if le => Some(u64::from_le_bytes(data.try_into().unwrap_or_default())),
+            8 => Some(u64::from_be_bytes(data.try_into().unwrap_or_default())),
+            _ => None,
+        };
+        let x86 = machine == EM_X86_64 || machine == EM_386;
+        let (name, bits) = match pr_type {
+            GNU_PROPERTY_STACK_SIZE => ("GNU_PROPERTY_STACK_SIZE", None),
+            GNU_PROPERTY_NO_COPY_ON_PROTECTED => ("GNU_PROPERTY_NO_COPY_ON_PROTECTED", None),
+            GNU_PROPERTY_1_NEEDED => ("GNU_PROPERTY_1_NEEDED", Some(PROPERTY_1_NEEDED)),
+            GNU_PROPERTY_X86_FEATURE_1_AND if x86 => {
+                ("GNU_PROPERTY_X86_FEATURE_1_AND", Some(X86_FEATURE_1))
+            }
+            GNU_PROPERTY_X86_FEATURE_2_NEEDED if x86 => {
+                ("GNU_PROPERTY_X86_FEATURE_2_NEEDED", Some(X86_FEATURE_2))
+            }
+            GNU_PROPERTY_X86_FEATURE_2_USED if x86 => {
+                ("GNU_PROPERTY_X86_FEATURE_2_USED", Some(X86_FEATURE_2))
+            }
+            GNU_PROPERTY_X86_ISA_1_NEEDED if x86 => {
+                ("GNU_PROPERTY_X86_ISA_1_NEEDED", Some(X86_ISA_1))
+            }
+            GNU_PROPERTY_X86_ISA_1_USED if x86 => ("GNU_PROPERTY_X86_ISA_1_USED", Some(X86_ISA_1)),
+            GNU_PROPERTY_AARCH64_FEATURE_1_AND if machine == EM_AARCH64 => (
+                "GNU_PROPERTY_AARCH64_FEATURE_1_AND",
+                Some(AARCH64_FEATURE_1),
+            ),
+            _ => ("", None),
+        };
+        result.push(GnuProperty {
+            r#type: pr_type,
+            name: match name {
+                "" => format!("{pr_type:#x}"),
+                name => name.to_string(),
+            },
+            features: match (bits, value) {
+                (Some(bits), Some(value)) => bit_names(value as u32, bits),
+                _ => vec![],
+            },
+            value,
+        });
+        off += 8 + size.next_multiple_of(align);
+    }
+    result
+}
+
+fn bit_names(value: u32, names: &[&str]) -> Vec<String> {
+    (0..32)
+        .filter(|bit| value & (1 << bit) != 0)
+        .map(|bit| match names.get(bit) {
+            Some(name) => name.to_string(),
+            None => format!("{:#x}", 1u32 << bit),
+        })
+        .collect()
+}
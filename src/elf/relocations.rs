@@ -0,0 +1,308 @@
+use crate::{
+    elf::segments::vaddr_to_offset,
+    util::{read_uint, safe_read},
+};
+use goblin::elf::{
+    dynamic::DT_RELA,
+    header::{EM_AARCH64, EM_ARM, EM_MIPS, EM_PPC, EM_PPC64, EM_RISCV, ET_REL},
+    reloc::{r_to_str, Reloc},
+    section_header::{SHF_ALLOC, SHT_DYNSYM},
+    Elf,
+};
+use serde::{Deserialize, Serialize};
+
+const DT_RELRSZ: u64 = 35;
+const DT_RELR: u64 = 36;
+const SHT_RELR: u32 = 19;
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Relocation {
+    /// Address (or offset in the target section for relocatable objects) to patch
+    pub offset: u64,
+    pub r#type: u32,
+    /// Architecture specific name, e.g. `R_X86_64_JUMP_SLOT`
+    pub type_name: String,
+    pub addend: Option<i64>,
+    pub symbol: Option<String>,
+    /// Section containing `offset`
+    pub section: Option<String>,
+    /// Relocation table the entry comes from, e.g. `.rela.plt`
+    pub table: String,
+}
+
+/// GOT entry the dynamic linker fills with the address of an imported symbol.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct GotSlot {
+    pub symbol: String,
+    pub address: u64,
+    /// Lazily bound through the PLT (`JUMP_SLOT`) rather than `GLOB_DAT`
+    pub plt: bool,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Relocations {
+    pub relocations: Vec<Relocation>,
+    pub got_slots: Vec<GotSlot>,
+}
+
+impl TryFrom<(&Elf<'_>, &[u8])> for Relocations {
+    type Error = crate::error::Error;
+
+    fn try_from(elf: (&Elf, &[u8])) -> Result<Self, Self::Error> {
+        let (elf, data) = elf;
+        let mut result = Relocations::default();
+        let section_name = |idx: usize| {
+            elf.section_headers
+                .get(idx)
+                .and_then(|sh| elf.shdr_strtab.get_at(sh.sh_name))
+        };
+        if !elf.shdr_relocs.is_empty() {
+            for (idx, relocs) in &elf.shdr_relocs {
+                let sh = &elf.section_headers[*idx];
+                let dynamic = elf
+                    .section_headers
+                    .get(sh.sh_link as usize)
+                    .is_some_and(|link| link.sh_type == SHT_DYNSYM);
+                // in relocatable objects offsets are relative to the section in sh_info
+                let target =
+                    (elf.header.e_type == ET_REL).then(|| section_name(sh.sh_info as usize));
+                for reloc in relocs.iter() {
+                    result.push(
+                        elf,
+                        &reloc,
+                        dynamic,
+                        section_name(*idx).unwrap_or_default(),
+                        target,
+                    );
+                }
+            }
+        } else {
+            // no section headers, use the tables from PT_DYNAMIC
+            for reloc in elf.dynrelas.iter() {
+                result.push(elf, &reloc, true, ".rela.dyn", None);
+            }
+            for reloc in elf.dynrels.iter() {
+                result.push(elf, &reloc, true, ".rel.dyn", None);
+            }
+            let plt = if elf
+                .dynamic
+                .as_ref()
+                .is_some_and(|d| d.info.pltrel == DT_RELA)
+            {
+                ".rela.plt"
+            } else {
+                ".rel.plt"
+            };
+            for reloc in elf.pltrelocs.iter() {
+                result.push(elf, &reloc, true, plt, None);
+            }
+        }
+        result.push_relr(elf, data);
+        Ok(result)
+    }
+}
+
+impl Relocations {
+    fn push(
+        &mut self,
+        elf: &Elf,
+        reloc: &Reloc,
+        dynamic: bool,
+        table: &str,
+        target: Option<Option<&str>>,
+    ) {
+        let (syms, strtab) = if dynamic {
+            (&elf.dynsyms, &elf.dynstrtab)
+        } else {
+            (&elf.syms, &elf.strtab)
+        };
+        let symbol = (reloc.r_sym != 0)
+            .then(|| syms.get(reloc.r_sym))
+            .flatten()
+            .and_then(|sym| strtab.get_at(sym.st_name))
+            .filter(|name| !name.is_empty())
+            .map(|name| name.to_string());
+        let type_name = type_name(reloc.r_type, elf.header.e_machine);
+        if let Some(symbol) = &symbol {
+            if type_name.ends_with("JUMP_SLOT")
+                || type_name.ends_with("JMP_SLOT")
+                || type_name.ends_with("GLOB_DAT")
+            {
+                self.got_slots.push(GotSlot {
+                    symbol: symbol.clone(),
+                    address: reloc.r_offset,
+                    plt: !type_name.ends_with("GLOB_DAT"),
+                });
+            }
+        }
+        self.relocations.push(Relocation {
+            offset: reloc.r_offset,
+            r#type: reloc.r_type,
+            type_name,
+            addend: reloc.r_addend,
+            symbol,
+            section: match target {
+                Some(target) => target.map(|t| t.to_string()),
+                None => section_at(elf, reloc.r_offset),
+            },
+            table: table.to_string(),
+        });
+    }
+
+    /// Decodes the packed relative relocations of `.relr.dyn` (`DT_RELR`).
+    fn push_relr(&mut self, elf: &Elf, data: &[u8]) {
+        let mut tables: Vec<(String, &[u8])> = elf
+            .section_headers
+            .iter()
+            .filter(|sh| sh.sh_type == SHT_RELR)
+            .map(|sh| {
+                (
+                    elf.shdr_strtab
+                        .get_at(sh.sh_name)
+                        .unwrap_or_default()
+                        .to_string(),
+                    safe_read(data, sh.sh_offset as usize, sh.sh_size as usize),
+                )
+            })
+            .collect();
+        if tables.is_empty() {
+            if let Some(dynamic) = &elf.dynamic {
+                let tag = |tag| {
+                    dynamic
+                        .dyns
+                        .iter()
+                        .find(|d| d.d_tag == tag)
+                        .map(|d| d.d_val)
+                };
+                if let (Some(addr), Some(size)) = (tag(DT_RELR), tag(DT_RELRSZ)) {
+                    if let Some(offset) = vaddr_to_offset(elf, addr) {
+                        tables.push((
+                            ".relr.dyn".to_string(),
+                            safe_read(data, offset, size as usize),
+                        ));
+                    }
+                }
+            }
+        }
+        let word = if elf.is_64 { 8 } else { 4 };
+        let r_type = relative_type(elf.header.e_machine);
+        let type_name = type_name(r_type, elf.header.e_machine);
+        for (table, bytes) in tables {
+            for offset in relr_offsets(bytes, word, elf.little_endian) {
+                self.relocations.push(Relocation {
+                    offset,
+                    r#type: r_type,
+                    type_name: type_name.clone(),
+                    addend: None,
+                    symbol: None,
+                    section: section_at(elf, offset),
+                    table: table.clone(),
+                });
+            }
+        }
+    }
+}
+
+/// Addresses of a RELR table: an even entry is an address, an odd entry a bitmap of the
+/// words that follow the last address, one bit per word after the tag bit.
+fn relr_offsets(bytes: &[u8], word: usize, little_endian: bool) -> Vec<u64> {
+    let mut offsets = vec![];
+    let mut base = 0u64;
+    for entry in bytes.chunks_exact(word) {
+        let entry = read_uint(entry, 0, word, little_endian).unwrap_or_default();
+        if entry & 1 == 0 {
+            offsets.push(entry);
+            base = entry.wrapping_add(word as u64);
+        } else {
+            let bits = word as u64 * 8 - 1;
+            offsets.extend(
+                (0..bits)
+                    .filter(|bit| (entry >> (bit + 1)) & 1 != 0)
+                    .map(|bit| base.wrapping_add(bit * word as u64)),
+            );
+            base = base.wrapping_add(bits * word as u64);
+        }
+    }
+    offsets
+}
+
+fn type_name(r_type: u32, machine: u16) -> String {
+    match r_to_str(r_type, machine) {
+        name if name.starts_with("R_") => name.to_string(),
+        name => format!("R_{name}"),
+    }
+}
+
+/// `R_*_RELATIVE` type of the architecture, which RELR entries are a packed form of.
+fn relative_type(machine: u16) -> u32 {
+    match machine {
+        EM_AARCH64 => 1027,
+        EM_ARM => 23,
+        EM_PPC | EM_PPC64 => 22,
+        EM_RISCV | EM_MIPS => 3,
+        _ => 8,
+    }
+}
+
+/// Name of the allocated section containing `addr`.
+pub fn section_at(elf: &Elf, addr: u64) -> Option<String> {
+    elf.section_headers
+        .iter()
+        .filter(|sh| sh.sh_flags & SHF_ALLOC as u64 != 0)
+        .find(|sh| addr >= sh.sh_addr && addr - sh.sh_addr < sh.sh_size)
+        .and_then(|sh| elf.shdr_strtab.get_at(sh.sh_name))
+        .map(|name| name.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn table64(entries: &[u64]) -> Vec<u8> {
+        entries
+            .iter()
+            .flat_map(|entry| entry.to_le_bytes())
+            .collect()
+    }
+
+    #[test]
+    fn relr_address_and_bitmap() {
+        // 0x1000, then the words at 0x1008, 0x1018 and, after 63 words, 0x1000 + 64 * 8
+        let bytes = table64(&[0x1000, 0b101 << 1 | 1, 1 << 1 | 1]);
+        assert_eq!(
+            relr_offsets(&bytes, 8, true),
+            [0x1000, 0x1008, 0x1018, 0x1000 + 64 * 8]
+        );
+    }
+
+    #[test]
+    fn relr_32_bit_big_endian() {
+        let bytes: Vec<u8> = [0x2000u32, 0b11 << 1 | 1]
+            .iter()
+            .flat_map(|entry| entry.to_be_bytes())
+            .collect();
+        assert_eq!(relr_offsets(&bytes, 4, false), [0x2000, 0x2004, 0x2008]);
+    }
+
+    #[test]
+    fn relr_bitmap_without_address() {
+        // a bitmap before any address counts from 0, a full bitmap must not overflow
+        let bytes = table64(&[u64::MAX, u64::MAX]);
+        let offsets = relr_offsets(&bytes, 8, true);
+        assert_eq!(offsets.len(), 126);
+        assert_eq!(offsets[..2], [0, 8]);
+    }
+
+    #[test]
+    fn relr_truncated() {
+        let bytes = table64(&[0x1000, 0b11]);
+        assert_eq!(relr_offsets(&bytes[..12], 8, true), [0x1000]);
+        assert!(relr_offsets(&bytes[..7], 8, true).is_empty());
+    }
+
+    #[test]
+    fn relr_address_wraps() {
+        let bytes = table64(&[u64::MAX - 1, u64::MAX]);
+        assert_eq!(relr_offsets(&bytes, 8, true).len(), 64);
+    }
+}
@@ -0,0 +1,106 @@
+use crate::util::safe_read;
+use goblin::elf::{
+    program_header::{self, ProgramHeader, PF_R, PF_W, PF_X, PT_LOAD, PT_TLS},
+    section_header::{SectionHeader, SHF_ALLOC, SHF_TLS, SHT_NOBITS, SHT_NULL},
+    Elf,
+};
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, Default, PartialEq, PartialOrd, Serialize, Deserialize)]
+pub struct Segment {
+    pub r#type: String,
+    /// Permissions as `RWX`, with `-` for the missing ones.
+    pub flags: String,
+    pub offset: u64,
+    pub virt_addr: u64,
+    pub phys_addr: u64,
+    pub file_size: u64,
+    pub mem_size: u64,
+    pub align: u64,
+    pub entropy: Option<f32>,
+    /// Names of the sections contained in the segment.
+    pub sections: Vec<String>,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, PartialOrd, Serialize, Deserialize)]
+pub struct SegmentTable {
+    pub segments: Vec<Segment>,
+}
+
+impl TryFrom<(&Elf<'_>, &[u8])> for SegmentTable {
+    type Error = crate::error::Error;
+
+    fn try_from(elf: (&Elf, &[u8])) -> Result<Self, Self::Error> {
+        let mut result = SegmentTable { segments: vec![] };
+        for ph in &elf.0.program_headers {
+            let data = safe_read(elf.1, ph.p_offset as usize, ph.p_filesz as usize);
+            result.segments.push(Segment {
+                r#type: program_header::pt_to_str(ph.p_type).to_string(),
+                flags: segment_flags(ph.p_flags),
+                offset: ph.p_offset,
+                virt_addr: ph.p_vaddr,
+                phys_addr: ph.p_paddr,
+                file_size: ph.p_filesz,
+                mem_size: ph.p_memsz,
+                align: ph.p_align,
+                entropy: (!data.is_empty()).then(|| entropy::shannon_entropy(data)),
+                sections: elf
+                    .0
+                    .section_headers
+                    .iter()
+                    .filter(|sh| section_in_segment(sh, ph))
+                    .map(|sh| {
+                        elf.0
+                            .shdr_strtab
+                            .get_at(sh.sh_name)
+                            .unwrap_or_default()
+                            .to_string()
+                    })
+                    .collect(),
+            });
+        }
+        Ok(result)
+    }
+}
+
+/// Formats `p_flags` as `RWX`, with `-` for the missing permissions.
+pub fn segment_flags(flags: u32) -> String {
+    [(PF_R, 'R'), (PF_W, 'W'), (PF_X, 'X')]
+        .iter()
+        .map(|&(flag, c)| if flags & flag != 0 { c } else { '-' })
+        .collect()
+}
+
+/// Whether the section lies within the segment, the way `readelf -l` maps them.
+///
+/// Allocated sections are matched on their address, sections with file contents on
+/// their offset, `.tbss` only belongs to `PT_TLS`.
+fn section_in_segment(sh: &SectionHeader, ph: &ProgramHeader) -> bool {
+    if sh.sh_type == SHT_NULL {
+        return false;
+    }
+    let alloc = sh.sh_flags & SHF_ALLOC as u64 != 0;
+    let nobits = sh.sh_type == SHT_NOBITS;
+    if nobits && sh.sh_flags & SHF_TLS as u64 != 0 && ph.p_type != PT_TLS {
+        return false;
+    }
+    if !alloc && ph.p_type == PT_LOAD {
+        return false;
+    }
+    let contains = |start: u64, len: u64, seg_start: u64, seg_len: u64| {
+        let seg_end = seg_start.saturating_add(seg_len);
+        start >= seg_start && start.saturating_add(len) <= seg_end && (len > 0 || start < seg_end)
+    };
+    let in_file = nobits || contains(sh.sh_offset, sh.sh_size, ph.p_offset, ph.p_filesz);
+    let in_memory = !alloc || contains(sh.sh_addr, sh.sh_size, ph.p_vaddr, ph.p_memsz);
+    in_file && in_memory
+}
+
+/// Converts a virtual address to a file offset through the `PT_LOAD` segments.
+pub fn vaddr_to_offset(elf: &Elf, vaddr: u64) -> Option<usize> {
+    elf.program_headers
+        .iter()
+        .filter(|ph| ph.p_type == PT_LOAD)
+        .find(|ph| vaddr >= ph.p_vaddr && vaddr - ph.p_vaddr < ph.p_filesz)
+        .map(|ph| (vaddr - ph.p_vaddr + ph.p_offset) as usize)
+}
@@ -0,0 +1,132 @@
+use goblin::{
+    elf::{
+        section_header::{SHN_ABS, SHN_COMMON, SHN_UNDEF},
+        sym::{self, Sym, STB_GLOBAL, STB_GNU_UNIQUE, STB_WEAK, STV_DEFAULT, STV_PROTECTED},
+        Elf,
+    },
+    strtab::Strtab,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Symbol {
+    pub name: String,
+    /// Demangled C++ or Rust name, if the name is mangled
+    pub demangled: Option<String>,
+    /// Symbol version from `.gnu.version_d` (dynamic symbols only)
+    pub version: Option<String>,
+    pub binding: String,
+    pub r#type: String,
+    pub visibility: String,
+    pub value: u64,
+    pub size: u64,
+    /// Name of the section the symbol is defined in, `ABS` or `COMMON`
+    pub section: Option<String>,
+}
+
+/// Symbols exported through `.dynsym`
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Exports {
+    pub symbols: Vec<Symbol>,
+}
+
+/// Static symbols from `.symtab`
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct SymbolTable {
+    pub symbols: Vec<Symbol>,
+}
+
+impl TryFrom<&Elf<'_>> for Exports {
+    type Error = crate::error::Error;
+
+    fn try_from(elf: &Elf) -> Result<Self, Self::Error> {
+        // version index -> version name from .gnu.version_d
+        let mut definitions: HashMap<u16, &str> = HashMap::new();
+        if let Some(verdef) = &elf.verdef {
+            for def in verdef.iter() {
+                if let Some(aux) = def.iter().next() {
+                    let name = elf.dynstrtab.get_at(aux.vda_name).unwrap_or_default();
+                    definitions.insert(def.vd_ndx, name);
+                }
+            }
+        }
+        let symbols = elf
+            .dynsyms
+            .iter()
+            .enumerate()
+            .filter(|(_, sym)| is_export(sym))
+            .filter_map(|(idx, sym)| {
+                let version = elf
+                    .versym
+                    .as_ref()
+                    .and_then(|versym| versym.get_at(idx))
+                    .and_then(|v| definitions.get(&v.version()))
+                    .map(|v| v.to_string());
+                symbol(elf, &sym, &elf.dynstrtab, version)
+            })
+            .collect();
+        Ok(Exports { symbols })
+    }
+}
+
+impl SymbolTable {
+    /// Returns `None` when the binary has no `.symtab`, i.e. it is stripped.
+    pub fn parse(elf: &Elf) -> Option<Self> {
+        if elf.syms.is_empty() {
+            return None;
+        }
+        Some(SymbolTable {
+            symbols: elf
+                .syms
+                .iter()
+                .filter_map(|sym| symbol(elf, &sym, &elf.strtab, None))
+                .collect(),
+        })
+    }
+}
+
+/// Demangles C++ (Itanium) and Rust (legacy and v0) symbol names.
+pub fn demangle(name: &str) -> Option<String> {
+    // symbols in .symtab may carry a version suffix, e.g. `_ZdlPv@GLIBCXX_3.4`
+    let name = name.split('@').next().unwrap_or(name);
+    if let Ok(demangled) = rustc_demangle::try_demangle(name) {
+        return Some(format!("{demangled:#}"));
+    }
+    cpp_demangle::Symbol::new(name)
+        .ok()
+        .and_then(|sym| sym.demangle(&Default::default()).ok())
+}
+
+fn is_export(sym: &Sym) -> bool {
+    let bind = sym.st_bind();
+    let visibility = sym.st_visibility();
+    sym.st_shndx != SHN_UNDEF as usize
+        && matches!(bind, STB_GLOBAL | STB_WEAK | STB_GNU_UNIQUE)
+        && matches!(visibility, STV_DEFAULT | STV_PROTECTED)
+}
+
+fn symbol(elf: &Elf, sym: &Sym, strtab: &Strtab, version: Option<String>) -> Option<Symbol> {
+    let name = strtab.get_at(sym.st_name).filter(|n| !n.is_empty())?;
+    let section = match sym.st_shndx as u32 {
+        SHN_UNDEF => None,
+        SHN_ABS => Some("ABS".to_string()),
+        SHN_COMMON => Some("COMMON".to_string()),
+        idx => elf
+            .section_headers
+            .get(idx as usize)
+            .and_then(|sh| elf.shdr_strtab.get_at(sh.sh_name))
+            .map(|name| name.to_string()),
+    };
+    Some(Symbol {
+        name: name.to_string(),
+        demangled: demangle(name),
+        version,
+        binding: sym::bind_to_str(sym.st_bind()).to_string(),
+        r#type: sym::type_to_str(sym.st_type()).to_string(),
+        visibility: sym::visibility_to_str(sym.st_visibility()).to_string(),
+        value: sym.st_value,
+        size: sym.st_size,
+        section,
+    })
+}
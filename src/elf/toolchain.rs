@@ -0,0 +1,202 @@
+use crate::{
+    elf::{notes::NoteValue, ElfFileInformation},
+    util::safe_read,
+};
+use goblin::elf::{section_header::SHT_NOBITS, Elf};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Compiler, linker and runtime guessed from the traces they leave in an ELF
+/// file, the counterpart of `RichToolchain` for PE files.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Toolchain {
+    /// Distinct compilers, e.g. `GCC 12.2.0` or `Clang 17.0.6`
+    pub compilers: Vec<String>,
+    /// e.g. `LLD 17.0.6`, `gold 1.16` or `mold 2.4.0`
+    pub linker: Option<String>,
+    /// Languages with runtime traces, e.g. `Go`, `Rust` or `C++`
+    pub languages: Vec<String>,
+    /// Version the Go toolchain recorded in `.go.buildinfo`, e.g. `go1.21.5`
+    pub go_version: Option<String>,
+    /// From `.comment`, e.g. `1.75.0`
+    pub rust_version: Option<String>,
+    /// Commit hash of rustc, from the `/rustc/<hash>/` paths in panic messages
+    pub rustc_commit: Option<String>,
+    /// `glibc`, `musl`, `uClibc` or `bionic`
+    pub libc: Option<String>,
+    /// Raw `.comment` strings
+    pub comments: Vec<String>,
+    /// One line description, e.g. `built with GCC 12.2.0, C++, glibc`.
+    pub summary: String,
+}
+
+impl Toolchain {
+    pub fn parse(elf: (&Elf, &[u8]), info: &ElfFileInformation) -> Toolchain {
+        let mut toolchain = Toolchain {
+            comments: section(elf, ".comment")
+                .map(|bytes| {
+                    bytes
+                        .split(|&b| b == 0)
+                        .filter(|s| !s.is_empty())
+                        .map(|s| String::from_utf8_lossy(s).trim().to_string())
+                        .collect()
+                })
+                .unwrap_or_default(),
+            ..Default::default()
+        };
+        for comment in toolchain.comments.clone() {
+            // Linker: LLD 17.0.6 (https://github.com/llvm/llvm-project ...)
+            if let Some(linker) = comment.strip_prefix("Linker: ") {
+                toolchain.linker = linker.split(" (").next().map(|s| s.to_string());
+            } else if comment.starts_with("mold ") {
+                toolchain.linker = comment.split(" (").next().map(|s| s.to_string());
+            } else if let Some(version) = comment.strip_prefix("rustc version ") {
+                toolchain.rust_version = version.split_whitespace().next().map(|s| s.to_string());
+            } else if let Some(compiler) = compiler(&comment) {
+                toolchain.push_compiler(compiler);
+            }
+        }
+        for producer in info
+            .debug_info
+            .compile_units
+            .iter()
+            .filter_map(|cu| cu.producer.as_ref())
+        {
+            if let Some(compiler) = compiler(producer) {
+                toolchain.push_compiler(compiler);
+            }
+        }
+        for note in info.notes.notes.iter() {
+            match &note.value {
+                NoteValue::GoldVersion(version) => {
+                    toolchain.linker.get_or_insert_with(|| version.clone());
+                }
+                NoteValue::Android { .. } => {
+                    toolchain.libc = Some("bionic".to_string());
+                }
+                _ => {}
+            }
+        }
+
+        toolchain.go_version = info
+            .go
+            .as_ref()
+            .map(|go| go.version.clone())
+            .filter(|version| !version.is_empty());
+        let go = info.go.is_some()
+            || info
+                .notes
+                .notes
+                .iter()
+                .any(|n| matches!(n.value, NoteValue::GoBuildId(_)));
+        toolchain.rustc_commit = info
+            .rust
+            .as_ref()
+            .and_then(|rust| rust.rustc_commit.clone());
+        let rust = info.rust.is_some();
+        let cpp = info.dynamic.needed.iter().any(|lib| {
+            lib.starts_with("libstdc++") || lib.starts_with("libc++.") || lib.starts_with("libc++_")
+        });
+        for (present, language) in [(go, "Go"), (rust, "Rust"), (cpp, "C++")] {
+            if present {
+                toolchain.languages.push(language.to_string());
+            }
+        }
+
+        if toolchain.libc.is_none() {
+            toolchain.libc = libc(info);
+        }
+        toolchain.summary = toolchain.to_string();
+        toolchain
+    }
+
+    fn push_compiler(&mut self, compiler: String) {
+        if !self.compilers.contains(&compiler) {
+            self.compilers.push(compiler);
+        }
+    }
+}
+
+impl fmt::Display for Toolchain {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let compiler = match (&self.go_version, &self.rust_version, self.compilers.first()) {
+            (Some(go), _, _) => go.clone(),
+            (None, Some(rust), _) => format!("rustc {}", rust),
+            (None, None, Some(compiler)) => compiler.clone(),
+            (None, None, None) => "an unknown toolchain".to_string(),
+        };
+        write!(f, "built with {}", compiler)?;
+        if let Some(linker) = &self.linker {
+            write!(f, " and {}", linker)?;
+        }
+        for language in self.languages.iter() {
+            write!(f, ", {}", language)?;
+        }
+        if let Some(libc) = &self.libc {
+            write!(f, ", {}", libc)?;
+        }
+        Ok(())
+    }
+}
+
+/// Compiler name and version from a `.comment` string or a DWARF producer.
+fn compiler(comment: &str) -> Option<String> {
+    // GCC: (Ubuntu 9.3.0-17ubuntu1~20.04) 9.3.0
+    // GNU C17 12.2.0 -mtune=generic -O2
+    if let Some(rest) = comment.strip_prefix("GCC: ") {
+        let version = match rest.strip_prefix('(') {
+            Some(rest) => rest.split_once(") ").map(|(_, v)| v)?,
+            None => rest,
+        };
+        return Some(format!("GCC {}", version.split_whitespace().next()?));
+    }
+    if comment.starts_with("GNU ") && !comment.starts_with("GNU AS") {
+        let version = comment.split_whitespace().nth(2)?;
+        if version.starts_with(|c: char| c.is_ascii_digit()) {
+            return Some(format!("GCC {}", version));
+        }
+    }
+    // Ubuntu clang version 14.0.0-1ubuntu1, Android (...) clang version 17.0.2
+    if let Some((_, rest)) = comment.split_once("clang version ") {
+        return Some(format!("Clang {}", rest.split_whitespace().next()?));
+    }
+    if comment.starts_with("Intel(R)") || comment.starts_with("GHC ") {
+        return Some(comment.to_string());
+    }
+    None
+}
+
+/// Contents of the section called `name`.
+fn section<'a>(elf: (&Elf, &'a [u8]), name: &str) -> Option<&'a [u8]> {
+    elf.0
+        .section_headers
+        .iter()
+        .filter(|sh| sh.sh_type != SHT_NOBITS)
+        .find(|sh| elf.0.shdr_strtab.get_at(sh.sh_name) == Some(name))
+        .map(|sh| safe_read(elf.1, sh.sh_offset as usize, sh.sh_size as usize))
+}
+
+fn libc(info: &ElfFileInformation) -> Option<String> {
+    let interpreter = info.dynamic.interpreter.as_deref().unwrap_or_default();
+    let needed = |prefix: &str| info.dynamic.needed.iter().any(|l| l.starts_with(prefix));
+    let libc = if interpreter.contains("ld-musl") || needed("libc.musl") {
+        "musl"
+    } else if interpreter.contains("ld-uClibc") || needed("libc.so.0") {
+        "uClibc"
+    } else if interpreter.starts_with("/system/bin/linker") {
+        "bionic"
+    } else if interpreter.contains("ld-linux") || needed("libc.so.6") {
+        "glibc"
+    } else if info
+        .notes
+        .notes
+        .iter()
+        .any(|n| matches!(n.value, NoteValue::AbiTag { .. }))
+    {
+        // static glibc binaries keep the ABI tag note of its crt1.o
+        "glibc"
+    } else {
+        return None;
+    };
+    Some(libc.to_string())
+}
@@ -0,0 +1,111 @@
+use goblin::elf::Elf;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+
+const VER_FLG_BASE: u16 = 0x1;
+const VER_FLG_WEAK: u16 = 0x2;
+
+/// Version defined in `.gnu.version_d`, e.g. `GLIBC_2.34` in libc.so.6.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct VersionDefinition {
+    /// Index symbols refer to through `.gnu.version`
+    pub index: u16,
+    pub name: String,
+    /// Versions this one inherits from
+    pub parents: Vec<String>,
+    /// The definition naming the object itself (its soname)
+    pub base: bool,
+    pub weak: bool,
+}
+
+/// Version required from a library, from `.gnu.version_r`.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct VersionRequirement {
+    pub index: u16,
+    pub name: String,
+    pub weak: bool,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct LibraryRequirements {
+    pub library: String,
+    pub versions: Vec<VersionRequirement>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct SymbolVersions {
+    pub definitions: Vec<VersionDefinition>,
+    pub requirements: Vec<LibraryRequirements>,
+    /// Highest `GLIBC_*` version required, the oldest glibc the binary runs on
+    pub glibc: Option<String>,
+    /// Highest `GLIBCXX_*` version required from libstdc++
+    pub glibcxx: Option<String>,
+    /// Highest `CXXABI_*` version required from libstdc++
+    pub cxxabi: Option<String>,
+}
+
+impl TryFrom<&Elf<'_>> for SymbolVersions {
+    type Error = crate::error::Error;
+
+    fn try_from(elf: &Elf) -> Result<Self, Self::Error> {
+        let name = |offset: usize| elf.dynstrtab.get_at(offset).unwrap_or_default().to_string();
+        let mut result = SymbolVersions::default();
+        if let Some(verdef) = &elf.verdef {
+            for def in verdef.iter() {
+                let mut names = def.iter().map(|aux| name(aux.vda_name));
+                result.definitions.push(VersionDefinition {
+                    index: def.vd_ndx,
+                    name: names.next().unwrap_or_default(),
+                    parents: names.collect(),
+                    base: def.vd_flags & VER_FLG_BASE != 0,
+                    weak: def.vd_flags & VER_FLG_WEAK != 0,
+                });
+            }
+        }
+        if let Some(verneed) = &elf.verneed {
+            for need in verneed.iter() {
+                result.requirements.push(LibraryRequirements {
+                    library: name(need.vn_file),
+                    versions: need
+                        .iter()
+                        .map(|aux| VersionRequirement {
+                            index: aux.vna_other,
+                            name: name(aux.vna_name),
+                            weak: aux.vna_flags & VER_FLG_WEAK != 0,
+                        })
+                        .collect(),
+                });
+            }
+        }
+        result.glibc = result.highest("GLIBC_");
+        result.glibcxx = result.highest("GLIBCXX_");
+        result.cxxabi = result.highest("CXXABI_");
+        Ok(result)
+    }
+}
+
+impl SymbolVersions {
+    /// Highest required version named `<prefix><major>.<minor>...`, ignoring
+    /// non numeric ones like `GLIBC_PRIVATE` or `GLIBC_ABI_DT_RELR`.
+    fn highest(&self, prefix: &str) -> Option<String> {
+        self.requirements
+            .iter()
+            .flat_map(|lib| lib.versions.iter())
+            .filter_map(|v| Some((&v.name, version_number(v.name.strip_prefix(prefix)?)?)))
+            .max_by(|a, b| compare(&a.1, &b.1))
+            .map(|(name, _)| name.clone())
+    }
+}
+
+fn version_number(version: &str) -> Option<Vec<u32>> {
+    version.split('.').map(|part| part.parse().ok()).collect()
+}
+
+fn compare(a: &[u32], b: &[u32]) -> Ordering {
+    // missing components count as 0, `2.2` == `2.2.0`
+    let len = a.len().max(b.len());
+    (0..len)
+        .map(|i| a.get(i).unwrap_or(&0).cmp(b.get(i).unwrap_or(&0)))
+        .find(|o| o.is_ne())
+        .unwrap_or(Ordering::Equal)
+}
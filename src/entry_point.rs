@@ -1,12 +1,58 @@
-use crate::sections::{Section, SectionTable};
+use crate::{
+    database::SignatureDatabase,
+    elf::segments::vaddr_to_offset,
+    packers::{PackerDatabase, SignatureKind},
+    pe::util::rva_to_offset,
+    sections::{Section, SectionTable},
+    util::safe_read,
+};
 use goblin::pe::PE;
 use serde::{Deserialize, Serialize};
+use std::sync::RwLock;
+
+const USERDB: &str = include_str!("../assets/userdb.txt");
+
+/// Number of bytes read at the entry point.
+pub const ENTRY_POINT_BYTES: usize = 64;
+
+lazy_static::lazy_static! {
+    /// Loaded entry point databases, later ones take precedence.
+    static ref EP_DATABASES: RwLock<Vec<EntryPointDatabase>> =
+        RwLock::new(vec![EntryPointDatabase::builtin()]);
+}
 
 #[derive(Clone, Debug, Default, PartialEq, PartialOrd, Serialize, Deserialize)]
 pub struct EntryPoint {
     pub address: u64,
     pub section: Option<Section>,
+    /// File offset of the entry point
+    #[serde(default)]
+    pub offset: Option<u64>,
+    /// First `ENTRY_POINT_BYTES` bytes at the entry point
+    #[serde(default)]
+    pub bytes: Vec<u8>,
+    /// Matching entry point signatures, including those of the packer database
+    #[serde(default)]
+    pub signatures: Vec<SignatureMatch>,
 }
+
+impl EntryPoint {
+    fn new(address: u64, section: Option<Section>, offset: Option<usize>, data: &[u8]) -> Self {
+        // DLLs and shared objects without an entry point have an address of 0
+        let offset = offset.filter(|_| address != 0);
+        let bytes = offset
+            .map(|offset| safe_read(data, offset, ENTRY_POINT_BYTES).to_vec())
+            .unwrap_or_default();
+        EntryPoint {
+            address,
+            section,
+            offset: offset.map(|offset| offset as u64),
+            signatures: EntryPointDatabase::matching(&bytes, data),
+            bytes,
+        }
+    }
+}
+
 impl TryFrom<(&goblin::pe::PE<'_>, &[u8])> for EntryPoint {
     type Error = crate::error::Error;
 
@@ -17,28 +63,213 @@ impl TryFrom<(&goblin::pe::PE<'_>, &[u8])> for EntryPoint {
             entry_point >= (section.virt_addr)
                 && ((entry_point) < (section.virt_size + section.virt_addr))
         });
-        Ok(EntryPoint {
-            address: entry_point,
-            section: entry_section,
-        })
+        let offset = rva_to_offset(pe.0, pe.0.entry as u32);
+        Ok(EntryPoint::new(entry_point, entry_section, offset, pe.1))
     }
 }
 
-impl TryFrom<&goblin::elf::Elf<'_>> for EntryPoint {
+impl TryFrom<(&goblin::elf::Elf<'_>, &[u8])> for EntryPoint {
     type Error = crate::error::Error;
 
-    fn try_from(elf: &goblin::elf::Elf) -> Result<Self, Self::Error> {
-        let entry_point = elf.entry;
+    fn try_from(elf: (&goblin::elf::Elf, &[u8])) -> Result<Self, Self::Error> {
+        let entry_point = elf.0.entry;
         let entry_section = elf
+            .0
             .section_headers
             .iter()
             .find(|section| {
                 entry_point >= section.sh_addr && entry_point < section.sh_addr + section.sh_size
             })
             .map(|section| section.into());
-        Ok(EntryPoint {
-            address: entry_point,
-            section: entry_section,
-        })
+        let offset = vaddr_to_offset(elf.0, entry_point);
+        Ok(EntryPoint::new(entry_point, entry_section, offset, elf.1))
+    }
+}
+
+/// Hex byte pattern where `??` matches any byte.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct BytePattern(pub Vec<Option<u8>>);
+
+impl BytePattern {
+    /// Parses space separated hex bytes, e.g. `60 BE ?? ?? ?? ??`.
+    pub fn parse(text: &str) -> Option<BytePattern> {
+        let pattern = text
+            .split_whitespace()
+            .map(|byte| match byte {
+                "??" => Some(None),
+                _ => u8::from_str_radix(byte, 16).ok().map(Some),
+            })
+            .collect::<Option<Vec<_>>>()?;
+        (!pattern.is_empty()).then_some(BytePattern(pattern))
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Whether `data` starts with the pattern.
+    // `Option::is_none_or` needs Rust 1.82
+    #[allow(clippy::unnecessary_map_or)]
+    pub fn matches_at(&self, data: &[u8]) -> bool {
+        data.len() >= self.0.len()
+            && self
+                .0
+                .iter()
+                .zip(data)
+                .all(|(p, b)| p.map_or(true, |p| p == *b))
+    }
+
+    /// Offset of the first match in `data`.
+    pub fn find_in(&self, data: &[u8]) -> Option<usize> {
+        let last = data.len().checked_sub(self.0.len())?;
+        // only offsets where the first fixed byte of the pattern occurs can match
+        let Some((anchor, byte)) = self
+            .0
+            .iter()
+            .enumerate()
+            .find_map(|(index, byte)| byte.map(|byte| (index, byte)))
+        else {
+            return Some(0);
+        };
+        let mut offset = 0;
+        while offset <= last {
+            offset += data[offset + anchor..=last + anchor]
+                .iter()
+                .position(|&b| b == byte)?;
+            if self.matches_at(&data[offset..]) {
+                return Some(offset);
+            }
+            offset += 1;
+        }
+        None
+    }
+}
+
+impl From<&[u8]> for BytePattern {
+    fn from(bytes: &[u8]) -> Self {
+        BytePattern(bytes.iter().copied().map(Some).collect())
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, PartialOrd, Serialize, Deserialize)]
+pub struct SignatureMatch {
+    pub name: String,
+    /// Version of the database the signature comes from
+    pub database: String,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct EntryPointSignature {
+    pub name: String,
+    pub pattern: BytePattern,
+    /// Match at the entry point only, anywhere in the file otherwise
+    pub ep_only: bool,
+}
+
+/// A PEiD `userdb.txt` style signature database.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct EntryPointDatabase {
+    pub version: String,
+    pub signatures: Vec<EntryPointSignature>,
+}
+
+impl SignatureDatabase for EntryPointDatabase {
+    const BUILTIN: &'static str = USERDB;
+    const COMMENT: char = ';';
+
+    fn parse(version: &str, data: &str) -> EntryPointDatabase {
+        let mut signatures = vec![];
+        let mut name: Option<&str> = None;
+        let mut pattern: Option<BytePattern> = None;
+        let mut push = |name: Option<&str>, pattern: Option<BytePattern>, ep_only: bool| {
+            if let (Some(name), Some(pattern)) = (name, pattern) {
+                signatures.push(EntryPointSignature {
+                    name: name.to_string(),
+                    pattern,
+                    ep_only,
+                });
+            }
+        };
+        for line in data.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with(';') {
+                continue;
+            }
+            if let Some(section) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+                // a signature without `ep_only` defaults to matching at the entry point
+                push(name, pattern.take(), true);
+                name = Some(section.trim());
+                continue;
+            }
+            let Some((key, value)) = line.split_once('=') else {
+                continue;
+            };
+            match key.trim().to_ascii_lowercase().as_str() {
+                "signature" => pattern = BytePattern::parse(value),
+                "ep_only" => push(
+                    name,
+                    pattern.take(),
+                    value.trim().eq_ignore_ascii_case("true"),
+                ),
+                _ => {}
+            }
+        }
+        push(name, pattern, true);
+        EntryPointDatabase {
+            version: version.to_string(),
+            signatures,
+        }
+    }
+
+    fn databases() -> &'static RwLock<Vec<EntryPointDatabase>> {
+        &EP_DATABASES
+    }
+}
+
+impl EntryPointDatabase {
+    /// Names of the signatures matching the entry point bytes or the file, each reported once.
+    ///
+    /// Packers and protectors are identified by the entry point signatures of the packer
+    /// database.
+    fn matching(entry_point: &[u8], data: &[u8]) -> Vec<SignatureMatch> {
+        let databases = EntryPointDatabase::loaded();
+        let mut result: Vec<SignatureMatch> = vec![];
+        for database in databases.iter().rev() {
+            for signature in database.signatures.iter() {
+                if result.iter().any(|m| m.name == signature.name) {
+                    continue;
+                }
+                let matched = if signature.ep_only {
+                    signature.pattern.matches_at(entry_point)
+                } else {
+                    signature.pattern.find_in(data).is_some()
+                };
+                if matched {
+                    result.push(SignatureMatch {
+                        name: signature.name.clone(),
+                        database: database.version.clone(),
+                    });
+                }
+            }
+        }
+        for database in PackerDatabase::loaded().iter().rev() {
+            for signature in database.signatures.iter() {
+                if signature.kind != SignatureKind::EntryPoint
+                    || result.iter().any(|m| m.name == signature.name)
+                {
+                    continue;
+                }
+                if signature.pattern.matches_at(entry_point) {
+                    result.push(SignatureMatch {
+                        name: signature.name.clone(),
+                        database: database.version.clone(),
+                    });
+                }
+            }
+        }
+        result
     }
 }
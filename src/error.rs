@@ -13,6 +13,8 @@ pub enum Error {
 
     /// File does not exist
     FileNotFound,
+    /// Compression method or filter is not supported.
+    UnsupportedCompression,
     /// Null address.
     Null,
     /// Out of bounds.
@@ -0,0 +1,446 @@
+use crate::util::{c_string, find_all, read_uint, safe_read};
+use goblin::{
+    elf::{program_header::PT_LOAD, section_header::SHT_NOBITS, Elf},
+    pe::PE,
+};
+use serde::{Deserialize, Serialize};
+
+const BUILDINFO_MAGIC: &[u8] = b"\xff Go buildinf:";
+/// Length of the sentinels around the module info string
+const MODINFO_SENTINEL_SIZE: usize = 16;
+
+const PCLNTAB_MAGIC_12: u32 = 0xffff_fffb;
+const PCLNTAB_MAGIC_116: u32 = 0xffff_fffa;
+const PCLNTAB_MAGIC_118: u32 = 0xffff_fff0;
+const PCLNTAB_MAGIC_120: u32 = 0xffff_fff1;
+const PCLNTAB_MAGICS: [u32; 4] = [
+    PCLNTAB_MAGIC_120,
+    PCLNTAB_MAGIC_118,
+    PCLNTAB_MAGIC_116,
+    PCLNTAB_MAGIC_12,
+];
+
+/// Upper bound for the function count, anything above is a false positive
+const MAX_FUNCTIONS: usize = 4_000_000;
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct GoModule {
+    pub path: String,
+    pub version: String,
+    /// `h1:` hash from go.sum
+    pub sum: Option<String>,
+    /// Target of a `replace` directive
+    pub replace: Option<Box<GoModule>>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct GoBuildSetting {
+    pub key: String,
+    pub value: String,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct GoFunction {
+    pub name: String,
+    pub address: u64,
+}
+
+/// Metadata the Go toolchain embeds in every binary, survives stripping.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct GoInfo {
+    /// e.g. `go1.21.5`, empty when only the pclntab was found
+    pub version: String,
+    /// Package path of the main package, e.g. `github.com/docker/cli/cmd/docker`
+    pub path: Option<String>,
+    pub main_module: Option<GoModule>,
+    pub dependencies: Vec<GoModule>,
+    /// `build` lines, e.g. `-ldflags`, `CGO_ENABLED`, `GOOS` and `GOARCH`
+    pub settings: Vec<GoBuildSetting>,
+    /// Layout of the pclntab, e.g. `1.20` for Go 1.20 and later
+    pub pclntab_version: Option<String>,
+    /// Function names and entry points recovered from the pclntab
+    pub functions: Vec<GoFunction>,
+}
+
+/// Mapped region of the file, to follow the pointers in the build info.
+struct Region {
+    vaddr: u64,
+    size: u64,
+    offset: usize,
+}
+
+struct Image<'a> {
+    data: &'a [u8],
+    little_endian: bool,
+    /// Pointer size of the target, 4 or 8
+    ptr_size: usize,
+    regions: Vec<Region>,
+    buildinfo: Option<&'a [u8]>,
+    pclntab: Option<&'a [u8]>,
+    /// Parts of the file searched for the pclntab magic when there is no pclntab section
+    pclntab_search: Vec<&'a [u8]>,
+}
+
+impl GoInfo {
+    /// Returns `None` for binaries not built by the Go toolchain.
+    pub fn from_elf(elf: (&Elf, &[u8])) -> Option<GoInfo> {
+        let section = |names: &[&str]| {
+            elf.0
+                .section_headers
+                .iter()
+                .filter(|sh| sh.sh_type != SHT_NOBITS)
+                .find(|sh| {
+                    elf.0
+                        .shdr_strtab
+                        .get_at(sh.sh_name)
+                        .is_some_and(|name| names.contains(&name))
+                })
+                .map(|sh| safe_read(elf.1, sh.sh_offset as usize, sh.sh_size as usize))
+        };
+        GoInfo::parse(Image {
+            data: elf.1,
+            little_endian: elf.0.little_endian,
+            ptr_size: if elf.0.is_64 { 8 } else { 4 },
+            regions: elf
+                .0
+                .program_headers
+                .iter()
+                .filter(|ph| ph.p_type == PT_LOAD)
+                .map(|ph| Region {
+                    vaddr: ph.p_vaddr,
+                    size: ph.p_filesz,
+                    offset: ph.p_offset as usize,
+                })
+                .collect(),
+            buildinfo: section(&[".go.buildinfo"]),
+            pclntab: section(&[".gopclntab", ".data.rel.ro.gopclntab"]),
+            pclntab_search: vec![elf.1],
+        })
+    }
+
+    /// Returns `None` for binaries not built by the Go toolchain.
+    pub fn from_pe(pe: (&PE, &[u8])) -> Option<GoInfo> {
+        GoInfo::parse(Image {
+            data: pe.1,
+            little_endian: true,
+            ptr_size: if pe.0.is_64 { 8 } else { 4 },
+            regions: pe
+                .0
+                .sections
+                .iter()
+                .map(|sec| Region {
+                    vaddr: pe.0.image_base as u64 + sec.virtual_address as u64,
+                    size: sec.virtual_size.min(sec.size_of_raw_data) as u64,
+                    offset: sec.pointer_to_raw_data as usize,
+                })
+                .collect(),
+            // PE files have no dedicated sections, both are found by their magic
+            buildinfo: None,
+            pclntab: None,
+            // the linker puts the pclntab with the read-only data
+            pclntab_search: pe
+                .0
+                .sections
+                .iter()
+                .filter(|sec| matches!(sec.name(), Ok(".rdata" | ".data")))
+                .map(|sec| {
+                    safe_read(
+                        pe.1,
+                        sec.pointer_to_raw_data as usize,
+                        sec.size_of_raw_data as usize,
+                    )
+                })
+                .collect(),
+        })
+    }
+
+    fn parse(image: Image) -> Option<GoInfo> {
+        let mut info = GoInfo::default();
+        let buildinfo = match image.buildinfo {
+            Some(buildinfo) => image.build_info(buildinfo),
+            None => find_all(image.data, BUILDINFO_MAGIC)
+                .find_map(|offset| image.build_info(&image.data[offset..])),
+        };
+        let found_buildinfo = buildinfo.is_some();
+        if let Some((version, modinfo)) = buildinfo {
+            info.version = version;
+            info.parse_modinfo(&modinfo);
+        }
+        let pclntab = image.pclntab.and_then(|pclntab| image.functions(pclntab));
+        let pclntab = pclntab.or_else(|| {
+            PCLNTAB_MAGICS.iter().find_map(|magic| {
+                let le = magic.to_le_bytes();
+                let be = magic.to_be_bytes();
+                let pattern = if image.little_endian { le } else { be };
+                image.pclntab_search.iter().find_map(|region| {
+                    find_all(region, &pattern).find_map(|offset| image.functions(&region[offset..]))
+                })
+            })
+        });
+        if let Some((version, functions)) = pclntab {
+            info.pclntab_version = Some(version.to_string());
+            info.functions = functions;
+        } else if !found_buildinfo {
+            return None;
+        }
+        Some(info)
+    }
+
+    /// Parses the text from `runtime/debug.BuildInfo.String`.
+    fn parse_modinfo(&mut self, modinfo: &str) {
+        for line in modinfo.lines() {
+            let fields: Vec<&str> = line.split('\t').collect();
+            let module = || GoModule {
+                path: fields.get(1).unwrap_or(&"").to_string(),
+                version: fields.get(2).unwrap_or(&"").to_string(),
+                sum: fields
+                    .get(3)
+                    .filter(|s| !s.is_empty())
+                    .map(|s| s.to_string()),
+                replace: None,
+            };
+            match fields[0] {
+                "path" => self.path = fields.get(1).map(|s| s.to_string()),
+                "mod" => self.main_module = Some(module()),
+                "dep" => self.dependencies.push(module()),
+                "=>" => {
+                    // replaces the module on the previous line
+                    let last = self.dependencies.last_mut().or(self.main_module.as_mut());
+                    if let Some(last) = last {
+                        last.replace = Some(Box::new(module()));
+                    }
+                }
+                "build" => {
+                    let setting = fields[1..].join("\t");
+                    let (key, value) = split_setting(&setting);
+                    self.settings.push(GoBuildSetting { key, value });
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Value of the build setting `key`, e.g. `GOOS` or `-ldflags`.
+    pub fn setting(&self, key: &str) -> Option<&str> {
+        self.settings
+            .iter()
+            .find(|s| s.key == key)
+            .map(|s| s.value.as_str())
+    }
+}
+
+impl Image<'_> {
+    /// Version and module info from the build info blob at the start of `buildinfo`.
+    fn build_info(&self, buildinfo: &[u8]) -> Option<(String, String)> {
+        if !buildinfo.starts_with(BUILDINFO_MAGIC) || buildinfo.len() < 32 {
+            return None;
+        }
+        let ptr_size = buildinfo[14] as usize;
+        let flags = buildinfo[15];
+        if ptr_size != 4 && ptr_size != 8 {
+            return None;
+        }
+        let (version, modinfo) = if flags & 0x2 != 0 {
+            // Go 1.18+, two length prefixed strings right after the header
+            let (version, size) = read_varint_string(&buildinfo[32..])?;
+            let (modinfo, _) = read_varint_string(&buildinfo[32 + size..])?;
+            (version, modinfo)
+        } else {
+            // older releases store pointers to Go string headers
+            let big_endian = flags & 0x1 != 0;
+            let version = self.go_string(&buildinfo[16..], ptr_size, big_endian)?;
+            let modinfo = self
+                .go_string(&buildinfo[16 + ptr_size..], ptr_size, big_endian)
+                .unwrap_or_default();
+            (version, modinfo)
+        };
+        let version = String::from_utf8_lossy(version).to_string();
+        if !version.starts_with("go") && !version.starts_with("devel") {
+            return None;
+        }
+        let modinfo = match modinfo.len() {
+            len if len > 2 * MODINFO_SENTINEL_SIZE
+                && modinfo[len - MODINFO_SENTINEL_SIZE - 1] == b'\n' =>
+            {
+                &modinfo[MODINFO_SENTINEL_SIZE..len - MODINFO_SENTINEL_SIZE]
+            }
+            _ => modinfo,
+        };
+        Some((version, String::from_utf8_lossy(modinfo).to_string()))
+    }
+
+    /// Follows a pointer to a Go string header, `{data *byte; len int}`.
+    fn go_string(&self, ptr: &[u8], ptr_size: usize, big_endian: bool) -> Option<&[u8]> {
+        let header = self.vaddr_to_offset(read_uint(ptr, 0, ptr_size, !big_endian)?)?;
+        let header = self.data.get(header..)?;
+        let data = self.vaddr_to_offset(read_uint(header, 0, ptr_size, !big_endian)?)?;
+        let len = read_uint(header, ptr_size, ptr_size, !big_endian)? as usize;
+        Some(safe_read(self.data, data, len))
+    }
+
+    fn vaddr_to_offset(&self, vaddr: u64) -> Option<usize> {
+        self.regions
+            .iter()
+            .find(|r| vaddr >= r.vaddr && vaddr - r.vaddr < r.size)
+            .and_then(|r| r.offset.checked_add(usize::try_from(vaddr - r.vaddr).ok()?))
+    }
+
+    /// Function names and entry points of the pclntab at the start of `tab`.
+    fn functions(&self, tab: &[u8]) -> Option<(&'static str, Vec<GoFunction>)> {
+        let le = self.little_endian;
+        let magic = read_uint(tab, 0, 4, le)? as u32;
+        let (quantum, ptr_size) = (*tab.get(6)?, *tab.get(7)? as usize);
+        // the instruction size quantum is 1 on x86, 2 on s390x and 4 on RISC machines
+        if tab.get(4..6)? != [0, 0] || ![1, 2, 4].contains(&quantum) || ptr_size != self.ptr_size {
+            return None;
+        }
+        let word = |index: usize| read_uint(tab, 8 + index * ptr_size, ptr_size, le);
+        let u32_at = |offset: usize| read_uint(tab, offset, 4, le);
+        let mut functions = vec![];
+        let version = match magic {
+            PCLNTAB_MAGIC_118 | PCLNTAB_MAGIC_120 => {
+                // nfunc, nfiles, textStart, funcnameOffset, cuOffset, filetabOffset,
+                // pctabOffset, pclnOffset; the functab holds 32-bit offsets from textStart
+                let nfunc = word(0)? as usize;
+                let text_start = word(2)?;
+                let funcnames = word(3)? as usize;
+                let functab = word(7)? as usize;
+                if nfunc == 0 || nfunc > MAX_FUNCTIONS {
+                    return None;
+                }
+                for i in 0..nfunc {
+                    let entry_offset = functab.checked_add(i * 8)?;
+                    let entry = u32_at(entry_offset)?;
+                    let func =
+                        functab.checked_add(u32_at(entry_offset.checked_add(4)?)? as usize)?;
+                    let name_offset = u32_at(func.checked_add(4)?)? as usize;
+                    functions.push(GoFunction {
+                        name: c_string(tab.get(funcnames.checked_add(name_offset)?..)?),
+                        address: text_start.wrapping_add(entry),
+                    });
+                }
+                if magic == PCLNTAB_MAGIC_120 {
+                    "1.20"
+                } else {
+                    "1.18"
+                }
+            }
+            PCLNTAB_MAGIC_116 => {
+                // nfunc, nfiles, funcnameOffset, cuOffset, filetabOffset, pctabOffset, pclnOffset
+                let nfunc = word(0)? as usize;
+                let funcnames = word(2)? as usize;
+                let functab = word(6)? as usize;
+                if nfunc == 0 || nfunc > MAX_FUNCTIONS {
+                    return None;
+                }
+                for i in 0..nfunc {
+                    let entry_offset = functab.checked_add(i * 2 * ptr_size)?;
+                    let entry = read_uint(tab, entry_offset, ptr_size, le)?;
+                    let func = read_uint(tab, entry_offset.checked_add(ptr_size)?, ptr_size, le)?;
+                    let func = functab.checked_add(usize::try_from(func).ok()?)?;
+                    let name_offset = u32_at(func.checked_add(ptr_size)?)? as usize;
+                    functions.push(GoFunction {
+                        name: c_string(tab.get(funcnames.checked_add(name_offset)?..)?),
+                        address: entry,
+                    });
+                }
+                "1.16"
+            }
+            PCLNTAB_MAGIC_12 => {
+                // nfunc followed by the functab, names are relative to the table
+                let nfunc = word(0)? as usize;
+                let functab = 8 + ptr_size;
+                if nfunc == 0 || nfunc > MAX_FUNCTIONS {
+                    return None;
+                }
+                for i in 0..nfunc {
+                    let entry_offset = functab.checked_add(i * 2 * ptr_size)?;
+                    let entry = read_uint(tab, entry_offset, ptr_size, le)?;
+                    let func = read_uint(tab, entry_offset.checked_add(ptr_size)?, ptr_size, le)?;
+                    let func = usize::try_from(func).ok()?;
+                    let name_offset = u32_at(func.checked_add(ptr_size)?)? as usize;
+                    functions.push(GoFunction {
+                        name: c_string(tab.get(name_offset..)?),
+                        address: entry,
+                    });
+                }
+                "1.2"
+            }
+            _ => return None,
+        };
+        // a random match of the magic decodes to garbage, the runtime is always there
+        functions
+            .iter()
+            .any(|f| f.name.starts_with("runtime."))
+            .then_some((version, functions))
+    }
+}
+
+/// Splits `key=value`, unquoting Go quoted strings.
+fn split_setting(setting: &str) -> (String, String) {
+    let (key, value) = if setting.starts_with('"') {
+        let end = quoted_end(setting).unwrap_or(setting.len());
+        let (key, rest) = setting.split_at(end);
+        (
+            unquote(key),
+            rest.strip_prefix('=').unwrap_or(rest).to_string(),
+        )
+    } else {
+        let (key, value) = setting.split_once('=').unwrap_or((setting, ""));
+        (key.to_string(), value.to_string())
+    };
+    let value = if value.starts_with('"') {
+        unquote(&value)
+    } else {
+        value
+    };
+    (key, value)
+}
+
+/// Byte offset just past the closing quote.
+fn quoted_end(s: &str) -> Option<usize> {
+    let mut escaped = false;
+    for (i, c) in s.char_indices().skip(1) {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            '"' => return Some(i + 1),
+            _ => {}
+        }
+    }
+    None
+}
+
+fn unquote(s: &str) -> String {
+    let s = s.strip_prefix('"').unwrap_or(s);
+    let s = s.strip_suffix('"').unwrap_or(s);
+    let mut result = String::with_capacity(s.len());
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            result.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => result.push('\n'),
+            Some('t') => result.push('\t'),
+            Some('r') => result.push('\r'),
+            Some(c) => result.push(c),
+            None => {}
+        }
+    }
+    result
+}
+
+fn read_varint_string(bytes: &[u8]) -> Option<(&[u8], usize)> {
+    let mut len = 0u64;
+    for (i, &b) in bytes.iter().enumerate().take(10) {
+        len |= ((b & 0x7f) as u64) << (7 * i);
+        if b & 0x80 == 0 {
+            let start = i + 1;
+            let end = start.checked_add(len as usize)?;
+            return Some((bytes.get(start..end)?, end));
+        }
+    }
+    None
+}
@@ -0,0 +1,83 @@
+use super::{ArchiveMember, Listing};
+use crate::util::{le_u16, le_u32, nul_terminated};
+
+pub const CAB_MAGIC: &[u8] = b"MSCF";
+const CFHEADER_SIZE: usize = 36;
+const CFFOLDER_SIZE: usize = 8;
+const CFFILE_SIZE: usize = 16;
+const CFHDR_PREV_CABINET: u16 = 0x0001;
+const CFHDR_NEXT_CABINET: u16 = 0x0002;
+const CFHDR_RESERVE_PRESENT: u16 = 0x0004;
+const IFOLD_CONTINUED_FROM_PREV: u16 = 0xfffd;
+const IFOLD_CONTINUED_TO_NEXT: u16 = 0xfffe;
+const IFOLD_CONTINUED_PREV_AND_NEXT: u16 = 0xffff;
+
+/// Lists the files of a Microsoft Cabinet starting at `data[0]`.
+///
+/// Files share the compressed stream of their folder, so their packed size is unknown.
+pub fn list(data: &[u8]) -> Option<Listing> {
+    if !data.starts_with(CAB_MAGIC) {
+        return None;
+    }
+    let files_offset = le_u32(data, 16)? as usize;
+    let folders = le_u16(data, 26)?;
+    let files = le_u16(data, 28)?;
+    let flags = le_u16(data, 30)?;
+    let mut position = CFHEADER_SIZE;
+    let mut folder_reserve = 0;
+    if flags & CFHDR_RESERVE_PRESENT != 0 {
+        folder_reserve = *data.get(position + 2)? as usize;
+        position += 4 + le_u16(data, position)? as usize;
+    }
+    // names of the previous and next cabinet and disk of a set
+    for flag in [CFHDR_PREV_CABINET, CFHDR_NEXT_CABINET] {
+        if flags & flag != 0 {
+            for _ in 0..2 {
+                position += nul_terminated(data, position)?.len() + 1;
+            }
+        }
+    }
+    let compressions = (0..folders as usize)
+        .map(|index| {
+            let folder = position + index * (CFFOLDER_SIZE + folder_reserve);
+            le_u16(data, folder + 6).map(compression_name)
+        })
+        .collect::<Option<Vec<_>>>()?;
+    let mut members = vec![];
+    let mut position = files_offset;
+    for _ in 0..files {
+        let size = le_u32(data, position)?;
+        let folder = match le_u16(data, position + 8)? {
+            IFOLD_CONTINUED_FROM_PREV | IFOLD_CONTINUED_PREV_AND_NEXT => 0,
+            IFOLD_CONTINUED_TO_NEXT => compressions.len().saturating_sub(1),
+            folder => folder as usize,
+        };
+        let name = nul_terminated(data, position + CFFILE_SIZE)?;
+        let next = position + CFFILE_SIZE + name.len() + 1;
+        // names without the `_A_NAME_IS_UTF` attribute are in the OEM code page, only their
+        // ASCII characters are kept
+        let name = String::from_utf8_lossy(name).to_string();
+        position = next;
+        members.push(ArchiveMember {
+            name,
+            size: size as u64,
+            packed_size: None,
+            compression: compressions.get(folder).cloned().unwrap_or_default(),
+        });
+    }
+    Some(Listing {
+        members,
+        encrypted: false,
+    })
+}
+
+fn compression_name(kind: u16) -> String {
+    match kind & 0x000f {
+        0 => "stored".to_string(),
+        1 => "MSZIP".to_string(),
+        2 => "Quantum".to_string(),
+        // the window size is in bits 8 to 12
+        3 => format!("LZX:{}", (kind >> 8) & 0x1f),
+        _ => format!("unknown ({:#x})", kind),
+    }
+}
@@ -0,0 +1,331 @@
+use crate::{
+    packers::Overlay,
+    pe::util::{find_resource, ResourceId, RT_RCDATA},
+    util::{find, le_u16, le_u32, safe_read},
+};
+use goblin::pe::PE;
+use serde::{Deserialize, Serialize};
+
+pub mod cab;
+pub mod rar;
+pub mod sevenzip;
+pub mod zip;
+
+/// Section the WiX Burn engine keeps its container table in
+const WIXBURN_SECTION: &[u8] = b".wixburn";
+const BURN_SECTION_MAGIC: u32 = 0x00f1_4300;
+/// Start of the NSIS first header, after its flags
+const NSIS_SIGNATURE: &[u8] = b"\xef\xbe\xad\xdeNullsoftInst";
+const NSIS_FIRST_HEADER_SIZE: usize = 28;
+/// NSIS looks for its first header at 512 byte boundaries
+const NSIS_ALIGNMENT: usize = 512;
+const NSIS_FH_FLAGS_UNINSTALL: u32 = 1;
+const NSIS_FH_FLAGS_SILENT: u32 = 2;
+const INNO_SETUP_DATA: &[u8] = b"Inno Setup Setup Data (";
+/// Resource id of the Inno Setup loader offset table, 5.1.5 and later
+const INNO_OFFSET_TABLE_ID: u32 = 11111;
+const INNO_OFFSET_TABLE_MAGIC: &[u8] = b"rDlPtS";
+/// Older loaders point to the offset table from a header in the DOS stub
+const INNO_LOADER_HEADER_OFFSET: usize = 0x30;
+const INNO_LOADER_HEADER_MAGIC: u32 = 0x6f6e_6e49;
+const INSTALLSHIELD_MAGICS: [&[u8]; 2] = [b"InstallShield\0", b"ISSetupStream\0"];
+/// SFX stubs may put a configuration block before the archive
+const ARCHIVE_SEARCH_SIZE: usize = 0x10000;
+
+/// File stored in an installer or SFX archive.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct ArchiveMember {
+    pub name: String,
+    /// Uncompressed size
+    pub size: u64,
+    /// Size in the archive, unknown for members of solid blocks
+    pub packed_size: Option<u64>,
+    /// e.g. `Deflate`, `LZMA2` or `MSZIP`
+    pub compression: String,
+}
+
+/// Members of an archive and whether its directory is encrypted.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Listing {
+    pub members: Vec<ArchiveMember>,
+    pub encrypted: bool,
+}
+
+/// Installer or self-extracting archive wrapped around a stub executable.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Installer {
+    /// `NSIS`, `Inno Setup`, `InstallShield`, `WiX Burn`, `IExpress`, `7-Zip SFX`,
+    /// `WinRAR SFX`, `ZIP SFX` or `Cabinet SFX`
+    pub kind: String,
+    pub version: Option<String>,
+    /// Where the installer data was found, e.g. `overlay` or `resource CABINET`
+    pub source: String,
+    /// File offset of the installer data
+    pub offset: Option<u64>,
+    /// Compression of the installer data as a whole, when it is not per member
+    pub compression: Option<String>,
+    /// Other properties, e.g. NSIS header flags
+    pub details: Vec<String>,
+    /// Files the installer drops, empty when the format keeps the file list compressed
+    pub members: Vec<ArchiveMember>,
+    /// The archive directory is encrypted, so members cannot be listed
+    pub encrypted: bool,
+}
+
+impl Installer {
+    fn new(kind: &str, source: &str, offset: Option<usize>) -> Installer {
+        Installer {
+            kind: kind.to_string(),
+            source: source.to_string(),
+            offset: offset.map(|offset| offset as u64),
+            ..Default::default()
+        }
+    }
+
+    fn with_listing(mut self, listing: Listing) -> Installer {
+        self.members = listing.members;
+        self.encrypted = listing.encrypted;
+        self
+    }
+
+    /// Returns `None` when the file is neither an installer nor an SFX archive.
+    pub fn from_pe(pe: (&PE, &[u8]), overlay: Option<&Overlay>) -> Option<Installer> {
+        let data = pe.1;
+        let overlay = overlay.map(|overlay| {
+            let offset = overlay.offset as usize;
+            (offset, safe_read(data, offset, overlay.size as usize))
+        });
+        Installer::burn(pe)
+            .or_else(|| overlay.and_then(|(offset, overlay)| Installer::nsis(offset, overlay)))
+            .or_else(|| Installer::inno(pe))
+            .or_else(|| Installer::installshield(overlay))
+            .or_else(|| Installer::iexpress(pe))
+            .or_else(|| overlay.and_then(|(offset, overlay)| Installer::sfx(offset, overlay)))
+    }
+
+    /// Only self-extracting archives exist for ELF, the archive follows the stub.
+    pub fn from_elf(data: &[u8], overlay: Option<&Overlay>) -> Option<Installer> {
+        let offset = overlay?.offset as usize;
+        Installer::sfx(offset, safe_read(data, offset, overlay?.size as usize))
+    }
+
+    /// WiX Burn bundles describe their attached cabinet containers in `.wixburn`.
+    fn burn(pe: (&PE, &[u8])) -> Option<Installer> {
+        let section =
+            pe.0.sections
+                .iter()
+                .find(|section| section.name.split(|&b| b == 0).next() == Some(WIXBURN_SECTION))?;
+        let header = safe_read(
+            pe.1,
+            section.pointer_to_raw_data as usize,
+            section.size_of_raw_data as usize,
+        );
+        if le_u32(header, 0)? != BURN_SECTION_MAGIC {
+            return None;
+        }
+        let stub_size = le_u32(header, 24)? as usize;
+        let containers = le_u32(header, 44)? as usize;
+        let mut installer = Installer::new("WiX Burn", "section .wixburn", Some(stub_size));
+        installer.version = le_u32(header, 4).map(|version| version.to_string());
+        installer.compression = Some("Cabinet".to_string());
+        installer
+            .details
+            .push(format!("bundle id {}", guid(header.get(8..24)?)));
+        // the UX container with the bootstrapper application comes first
+        let mut offset = stub_size;
+        for index in 0..containers.min(header.len() / 4) {
+            let size = le_u32(header, 48 + index * 4)? as usize;
+            let container = safe_read(pe.1, offset, size);
+            installer.details.push(format!(
+                "container {} at {:#x}, {} bytes",
+                index, offset, size
+            ));
+            if let Some(listing) = cab::list(container) {
+                installer.members.extend(listing.members);
+            }
+            offset = offset.checked_add(size)?;
+        }
+        Some(installer)
+    }
+
+    /// NSIS appends a first header and its compressed script and files.
+    ///
+    /// The file list is part of the compressed script, so members are not listed.
+    fn nsis(overlay_offset: usize, overlay: &[u8]) -> Option<Installer> {
+        let start = (NSIS_ALIGNMENT - overlay_offset % NSIS_ALIGNMENT) % NSIS_ALIGNMENT;
+        let position = (start..overlay.len())
+            .step_by(NSIS_ALIGNMENT)
+            .find(|&pos| overlay.get(pos + 4..pos + 20) == Some(NSIS_SIGNATURE))?;
+        let header = &overlay[position..];
+        let flags = le_u32(header, 0)?;
+        let header_size = le_u32(header, 20)?;
+        let archive_size = le_u32(header, 24)?;
+        let mut installer = Installer::new("NSIS", "overlay", Some(overlay_offset + position));
+        let data = header.get(NSIS_FIRST_HEADER_SIZE..)?;
+        let compression = |stream: &[u8]| {
+            if stream.starts_with(&[0x5d, 0, 0]) {
+                Some("LZMA")
+            } else if stream.starts_with(b"1AY&SY") {
+                // NSIS strips the `BZh` stream header
+                Some("BZip2")
+            } else {
+                None
+            }
+        };
+        // non-solid archives prefix each block with its size, the top bit set when compressed
+        let (solid, compression) = match compression(data) {
+            Some(compression) => (true, compression),
+            None if le_u32(data, 0)? & 0x8000_0000 != 0 => {
+                (false, compression(&data[4..]).unwrap_or("Deflate"))
+            }
+            None => (true, "Deflate"),
+        };
+        installer.compression = Some(if solid {
+            format!("{} (solid)", compression)
+        } else {
+            compression.to_string()
+        });
+        installer
+            .details
+            .push(format!("header size {} bytes", header_size));
+        installer
+            .details
+            .push(format!("archive size {} bytes", archive_size));
+        if flags & NSIS_FH_FLAGS_UNINSTALL != 0 {
+            installer.details.push("uninstaller".to_string());
+        }
+        if flags & NSIS_FH_FLAGS_SILENT != 0 {
+            installer.details.push("silent".to_string());
+        }
+        Some(installer)
+    }
+
+    /// Inno Setup finds its data through an offset table, a resource since 5.1.5 and
+    /// pointed to from the loader header in the DOS stub before.
+    ///
+    /// The file list is in the compressed setup header, so members are not listed.
+    fn inno(pe: (&PE, &[u8])) -> Option<Installer> {
+        let table = find_resource(
+            pe.0,
+            pe.1,
+            ResourceId::Id(RT_RCDATA),
+            ResourceId::Id(INNO_OFFSET_TABLE_ID),
+        )
+        .or_else(|| {
+            let header = pe.1.get(INNO_LOADER_HEADER_OFFSET..)?;
+            let offset = le_u32(header, 4)?;
+            (le_u32(header, 0)? == INNO_LOADER_HEADER_MAGIC && le_u32(header, 8)? == !offset)
+                .then(|| pe.1.get(offset as usize..))
+                .flatten()
+        })?;
+        let (setup_e32, setup0, setup1) = inno_offsets(table)?;
+        pe.1.get(setup0..)?;
+        let mut installer = Installer::new("Inno Setup", "overlay", Some(setup0));
+        let id = safe_read(pe.1, setup0, 64);
+        if let Some(id) = id.strip_prefix(INNO_SETUP_DATA) {
+            let id = String::from_utf8_lossy(id.split(|&b| b == 0).next()?);
+            let mut parts = id.split(')');
+            installer.version = parts.next().map(|version| version.to_string());
+            if parts.any(|part| part.trim().trim_start_matches('(') == "u") {
+                installer.details.push("unicode".to_string());
+            }
+        }
+        installer
+            .details
+            .push(format!("setup.e32 at {:#x}", setup_e32));
+        installer.details.push(format!("setup-1 at {:#x}", setup1));
+        Some(installer)
+    }
+
+    /// InstallShield setup stubs append their files behind a stream header.
+    fn installshield(overlay: Option<(usize, &[u8])>) -> Option<Installer> {
+        let (offset, overlay) = overlay?;
+        if !INSTALLSHIELD_MAGICS
+            .iter()
+            .any(|magic| overlay.starts_with(magic))
+        {
+            return None;
+        }
+        let mut installer = Installer::new("InstallShield", "overlay", Some(offset));
+        installer
+            .details
+            .push(String::from_utf8_lossy(overlay.split(|&b| b == 0).next()?).to_string());
+        Some(installer)
+    }
+
+    /// IExpress packages keep a cabinet in the `CABINET` resource.
+    fn iexpress(pe: (&PE, &[u8])) -> Option<Installer> {
+        let cabinet = find_resource(
+            pe.0,
+            pe.1,
+            ResourceId::Id(RT_RCDATA),
+            ResourceId::Name("CABINET"),
+        )?;
+        let offset = cabinet.as_ptr() as usize - pe.1.as_ptr() as usize;
+        let listing = cab::list(cabinet)?;
+        let mut installer = Installer::new("IExpress", "resource CABINET", Some(offset));
+        if let Some(command) = find_resource(
+            pe.0,
+            pe.1,
+            ResourceId::Id(RT_RCDATA),
+            ResourceId::Name("RUNPROGRAM"),
+        ) {
+            let command = String::from_utf8_lossy(command.split(|&b| b == 0).next()?);
+            installer.details.push(format!("runs {}", command));
+        }
+        Some(installer.with_listing(listing))
+    }
+
+    /// Archives appended to a stub, the archive may follow an SFX configuration block.
+    fn sfx(overlay_offset: usize, overlay: &[u8]) -> Option<Installer> {
+        let head = safe_read(overlay, 0, ARCHIVE_SEARCH_SIZE);
+        if overlay.starts_with(cab::CAB_MAGIC) {
+            let installer = Installer::new("Cabinet SFX", "overlay", Some(overlay_offset));
+            return Some(installer.with_listing(cab::list(overlay)?));
+        }
+        if let Some(position) = find(head, sevenzip::SEVEN_ZIP_MAGIC) {
+            let offset = overlay_offset + position;
+            let installer = Installer::new("7-Zip SFX", "overlay", Some(offset));
+            return Some(installer.with_listing(sevenzip::list(&overlay[position..])?));
+        }
+        if let Some(position) = find(head, rar::RAR_MAGIC) {
+            let offset = overlay_offset + position;
+            let installer = Installer::new("WinRAR SFX", "overlay", Some(offset));
+            return Some(installer.with_listing(rar::list(&overlay[position..])?));
+        }
+        let position = find(head, zip::LOCAL_HEADER_MAGIC)?;
+        let installer = Installer::new("ZIP SFX", "overlay", Some(overlay_offset + position));
+        Some(installer.with_listing(zip::list(overlay)?))
+    }
+}
+
+/// Offsets of setup.e32, setup-0 and setup-1 from an Inno Setup offset table.
+///
+/// The fields before them changed between loader versions, which are told apart by the
+/// magic.
+fn inno_offsets(table: &[u8]) -> Option<(usize, usize, usize)> {
+    let magic = table.strip_prefix(INNO_OFFSET_TABLE_MAGIC)?;
+    let (setup_e32, setup0, setup1) = match magic.get(..2)? {
+        b"02" => (16, 36, 40),
+        b"04" | b"05" | b"06" => (16, 32, 36),
+        b"07" => (16, 28, 32),
+        _ => (20, 32, 36),
+    };
+    Some((
+        le_u32(table, setup_e32)? as usize,
+        le_u32(table, setup0)? as usize,
+        le_u32(table, setup1)? as usize,
+    ))
+}
+
+/// Formats a GUID stored in its little endian structure layout.
+fn guid(bytes: &[u8]) -> String {
+    format!(
+        "{{{:08x}-{:04x}-{:04x}-{}-{}}}",
+        le_u32(bytes, 0).unwrap_or_default(),
+        le_u16(bytes, 4).unwrap_or_default(),
+        le_u16(bytes, 6).unwrap_or_default(),
+        hex::encode(safe_read(bytes, 8, 2)),
+        hex::encode(safe_read(bytes, 10, 6)),
+    )
+}
@@ -0,0 +1,275 @@
+use super::{ArchiveMember, Listing};
+use crate::util::{le_u16, le_u32};
+
+/// Common start of the RAR 1.5 - 4.x and RAR 5 signatures
+pub const RAR_MAGIC: &[u8] = b"Rar!\x1a\x07";
+const RAR4_SIGNATURE_SIZE: usize = 7;
+const RAR5_SIGNATURE_SIZE: usize = 8;
+
+const RAR4_MAIN_HEAD: u8 = 0x73;
+const RAR4_FILE_HEAD: u8 = 0x74;
+const RAR4_END_HEAD: u8 = 0x7b;
+const RAR4_LONG_BLOCK: u16 = 0x8000;
+const RAR4_MHD_PASSWORD: u16 = 0x0080;
+const RAR4_LHD_PASSWORD: u16 = 0x0004;
+const RAR4_LHD_DIRECTORY: u16 = 0x00e0;
+const RAR4_LHD_LARGE: u16 = 0x0100;
+const RAR4_LHD_UNICODE: u16 = 0x0200;
+
+const RAR5_FILE_HEADER: u64 = 2;
+const RAR5_ENCRYPTION_HEADER: u64 = 4;
+const RAR5_END_HEADER: u64 = 5;
+const RAR5_HFL_EXTRA: u64 = 0x0001;
+const RAR5_HFL_DATA: u64 = 0x0002;
+const RAR5_FHFL_DIRECTORY: u64 = 0x0001;
+const RAR5_FHFL_UTIME: u64 = 0x0002;
+const RAR5_FHFL_CRC32: u64 = 0x0004;
+const RAR5_CI_SOLID: u64 = 0x0040;
+
+const METHODS: [&str; 6] = ["stored", "fastest", "fast", "normal", "good", "best"];
+
+/// Lists the files of a RAR archive starting at `data[0]`.
+pub fn list(data: &[u8]) -> Option<Listing> {
+    if !data.starts_with(RAR_MAGIC) {
+        return None;
+    }
+    match data.get(RAR_MAGIC.len()..RAR5_SIGNATURE_SIZE)? {
+        [0, _] => Some(list_rar4(data)),
+        [1, 0] => Some(list_rar5(data)),
+        _ => None,
+    }
+}
+
+/// Blocks of a 7 byte header: CRC, type, flags and size, and an optional data size.
+fn list_rar4(data: &[u8]) -> Listing {
+    let mut listing = Listing::default();
+    let mut position = RAR4_SIGNATURE_SIZE;
+    while let (Some(&kind), Some(flags), Some(size)) = (
+        data.get(position + 2),
+        le_u16(data, position + 3),
+        le_u16(data, position + 5),
+    ) {
+        let size = size as u64;
+        if size < 7 {
+            break;
+        }
+        let data_size = match kind {
+            RAR4_MAIN_HEAD if flags & RAR4_MHD_PASSWORD != 0 => {
+                listing.encrypted = true;
+                break;
+            }
+            RAR4_FILE_HEAD => {
+                let Some((member, packed_size)) = rar4_file(data, position, flags) else {
+                    break;
+                };
+                listing.members.extend(member);
+                packed_size
+            }
+            RAR4_END_HEAD => break,
+            _ if flags & RAR4_LONG_BLOCK != 0 => {
+                le_u32(data, position + 7).unwrap_or_default() as u64
+            }
+            _ => 0,
+        };
+        position = match (position as u64)
+            .checked_add(size)
+            .and_then(|next| next.checked_add(data_size))
+            .and_then(|next| usize::try_from(next).ok())
+        {
+            Some(next) => next,
+            None => break,
+        };
+    }
+    listing
+}
+
+/// File header, returns the member, `None` for directories, and the size of the packed data.
+fn rar4_file(data: &[u8], position: usize, flags: u16) -> Option<(Option<ArchiveMember>, u64)> {
+    let mut packed_size = le_u32(data, position + 7)? as u64;
+    let mut size = le_u32(data, position + 11)? as u64;
+    let method = *data.get(position + 25)?;
+    let name_size = le_u16(data, position + 26)? as usize;
+    let mut name_start = position + 32;
+    if flags & RAR4_LHD_LARGE != 0 {
+        packed_size |= (le_u32(data, position + 32)? as u64) << 32;
+        size |= (le_u32(data, position + 36)? as u64) << 32;
+        name_start += 8;
+    }
+    let mut name = data.get(name_start..name_start + name_size)?;
+    // unicode names follow the ASCII one after a NUL
+    if flags & RAR4_LHD_UNICODE != 0 {
+        name = name.split(|&b| b == 0).next()?;
+    }
+    if flags & RAR4_LHD_DIRECTORY == RAR4_LHD_DIRECTORY {
+        return Some((None, packed_size));
+    }
+    let mut compression = METHODS
+        .get(method.wrapping_sub(0x30) as usize)
+        .map(|method| method.to_string())
+        .unwrap_or_else(|| format!("unknown ({:#x})", method));
+    if flags & RAR4_LHD_PASSWORD != 0 {
+        compression.push_str(" (encrypted)");
+    }
+    let member = ArchiveMember {
+        name: String::from_utf8_lossy(name).to_string(),
+        size,
+        packed_size: Some(packed_size),
+        compression,
+    };
+    Some((Some(member), packed_size))
+}
+
+/// Headers of CRC, size, type and flags, all but the CRC variable length integers.
+fn list_rar5(data: &[u8]) -> Listing {
+    let mut listing = Listing::default();
+    let mut position = RAR5_SIGNATURE_SIZE;
+    while let Some(next) = rar5_header(data, position, &mut listing) {
+        position = next;
+    }
+    listing
+}
+
+/// Adds the member of a header, returns the position of the next header.
+fn rar5_header(data: &[u8], position: usize, listing: &mut Listing) -> Option<usize> {
+    let mut reader = VintReader {
+        data,
+        position: position + 4,
+    };
+    let size = reader.vint()?;
+    let start = reader.position;
+    let kind = reader.vint()?;
+    let flags = reader.vint()?;
+    if flags & RAR5_HFL_EXTRA != 0 {
+        reader.vint()?;
+    }
+    let data_size = if flags & RAR5_HFL_DATA != 0 {
+        reader.vint()?
+    } else {
+        0
+    };
+    match kind {
+        RAR5_FILE_HEADER => {
+            let file_flags = reader.vint()?;
+            let unpacked_size = reader.vint()?;
+            reader.vint()?;
+            if file_flags & RAR5_FHFL_UTIME != 0 {
+                reader.position += 4;
+            }
+            if file_flags & RAR5_FHFL_CRC32 != 0 {
+                reader.position += 4;
+            }
+            let info = reader.vint()?;
+            reader.vint()?;
+            let name_size = reader.vint()? as usize;
+            let name = data.get(reader.position..reader.position.checked_add(name_size)?)?;
+            if file_flags & RAR5_FHFL_DIRECTORY == 0 {
+                // version in bits 0 to 5, solid flag in bit 6, method in bits 7 to 9
+                let method = (info >> 7) & 7;
+                let mut compression = METHODS
+                    .get(method as usize)
+                    .map(|method| method.to_string())
+                    .unwrap_or_else(|| format!("unknown ({})", method));
+                if info & RAR5_CI_SOLID != 0 {
+                    compression.push_str(" (solid)");
+                }
+                listing.members.push(ArchiveMember {
+                    name: String::from_utf8_lossy(name).to_string(),
+                    size: unpacked_size,
+                    packed_size: Some(data_size),
+                    compression,
+                });
+            }
+        }
+        RAR5_ENCRYPTION_HEADER => {
+            listing.encrypted = true;
+            return None;
+        }
+        RAR5_END_HEADER => return None,
+        _ => {}
+    }
+    usize::try_from((start as u64).checked_add(size)?.checked_add(data_size)?).ok()
+}
+
+struct VintReader<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl VintReader<'_> {
+    /// Little endian groups of 7 bits, the top bit set on all but the last byte.
+    fn vint(&mut self) -> Option<u64> {
+        let mut value = 0u64;
+        for shift in (0..64).step_by(7) {
+            let byte = *self.data.get(self.position)?;
+            self.position += 1;
+            value |= ((byte & 0x7f) as u64) << shift;
+            if byte & 0x80 == 0 {
+                return Some(value);
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vint(data: &[u8]) -> Option<u64> {
+        VintReader { data, position: 0 }.vint()
+    }
+
+    #[test]
+    fn vint_sizes() {
+        assert_eq!(vint(&[0]), Some(0));
+        assert_eq!(vint(&[0x7f]), Some(0x7f));
+        assert_eq!(vint(&[0x80, 0x01]), Some(0x80));
+        assert_eq!(vint(&[0xff, 0xff, 0x03]), Some(0xffff));
+        let mut max = [0xff; 10];
+        max[9] = 0x01;
+        assert_eq!(vint(&max), Some(u64::MAX));
+    }
+
+    #[test]
+    fn vint_truncated_and_too_long() {
+        assert_eq!(vint(&[]), None);
+        assert_eq!(vint(&[0x80]), None);
+        assert_eq!(vint(&[0xff; 10]), None);
+        assert_eq!(vint(&[0xff; 11]), None);
+    }
+
+    #[test]
+    fn rar4_large_data_size() {
+        // a file header with LHD_LARGE and a data size of u64::MAX
+        let mut header = vec![0, 0, 0x74];
+        header.extend((RAR4_LONG_BLOCK | RAR4_LHD_LARGE).to_le_bytes());
+        header.extend(40u16.to_le_bytes());
+        header.extend(u32::MAX.to_le_bytes());
+        header.extend(u32::MAX.to_le_bytes());
+        header.extend([2, 0, 0, 0, 0, 0, 0, 0, 0, 29, 0x33, 1, 0, 0x20, 0, 0, 0]);
+        header.extend(u32::MAX.to_le_bytes());
+        header.extend(u32::MAX.to_le_bytes());
+        header.push(b'a');
+        let mut data = b"Rar!\x1a\x07\x00".to_vec();
+        data.extend(header);
+        let listing = list(&data).unwrap();
+        assert_eq!(listing.members.len(), 1);
+        assert_eq!(listing.members[0].packed_size, Some(u64::MAX));
+    }
+
+    #[test]
+    fn truncated_archives() {
+        let rar5 = b"Rar!\x1a\x07\x01\x00\0\0\0\0\x0a\x02\x02\x05";
+        for end in 0..rar5.len() {
+            if let Some(listing) = list(&rar5[..end]) {
+                assert!(listing.members.is_empty());
+            }
+        }
+        let rar4 = b"Rar!\x1a\x07\x00\0\0\x74\0\x80\x30\0";
+        for end in 0..rar4.len() {
+            if let Some(listing) = list(&rar4[..end]) {
+                assert!(listing.members.is_empty());
+            }
+        }
+    }
+}
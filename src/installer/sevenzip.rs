@@ -0,0 +1,701 @@
+use super::{ArchiveMember, Listing};
+use crate::util::{le_u32, le_u64, read_uint};
+use flate2::Crc;
+use lzma_rs::decompress::{Options, UnpackedSize};
+use std::io::{BufReader, Read};
+
+pub const SEVEN_ZIP_MAGIC: &[u8] = &[b'7', b'z', 0xbc, 0xaf, 0x27, 0x1c];
+const SIGNATURE_HEADER_SIZE: usize = 32;
+/// Upper bound for a decompressed header
+const MAX_HEADER_SIZE: u64 = 64 * 1024 * 1024;
+/// Headers of headers are not nested deeper than this in practice
+const MAX_HEADER_DEPTH: usize = 4;
+
+const K_END: u64 = 0x00;
+const K_HEADER: u64 = 0x01;
+const K_ARCHIVE_PROPERTIES: u64 = 0x02;
+const K_ADDITIONAL_STREAMS_INFO: u64 = 0x03;
+const K_MAIN_STREAMS_INFO: u64 = 0x04;
+const K_FILES_INFO: u64 = 0x05;
+const K_PACK_INFO: u64 = 0x06;
+const K_UNPACK_INFO: u64 = 0x07;
+const K_SUBSTREAMS_INFO: u64 = 0x08;
+const K_SIZE: u64 = 0x09;
+const K_CRC: u64 = 0x0a;
+const K_FOLDER: u64 = 0x0b;
+const K_CODERS_UNPACK_SIZE: u64 = 0x0c;
+const K_NUM_UNPACK_STREAM: u64 = 0x0d;
+const K_EMPTY_STREAM: u64 = 0x0e;
+const K_EMPTY_FILE: u64 = 0x0f;
+const K_NAME: u64 = 0x11;
+const K_ENCODED_HEADER: u64 = 0x17;
+
+const CODER_COPY: &[u8] = &[0x00];
+const CODER_LZMA: &[u8] = &[0x03, 0x01, 0x01];
+const CODER_LZMA2: &[u8] = &[0x21];
+const CODER_AES: &[u8] = &[0x06, 0xf1, 0x07, 0x01];
+
+/// Lists the files of a 7z archive starting at `data[0]`.
+///
+/// Files of a folder are compressed as one solid stream, so only files alone in their
+/// folder have a packed size.
+pub fn list(data: &[u8]) -> Option<Listing> {
+    if !data.starts_with(SEVEN_ZIP_MAGIC) {
+        return None;
+    }
+    // the start header CRC tells an archive from a stray signature
+    let mut crc = Crc::new();
+    crc.update(data.get(12..SIGNATURE_HEADER_SIZE)?);
+    if crc.sum() != le_u32(data, 8)? {
+        return None;
+    }
+    let offset = le_u64(data, 12)?;
+    let size = le_u64(data, 20)?;
+    let start = usize::try_from(offset.checked_add(SIGNATURE_HEADER_SIZE as u64)?).ok()?;
+    let mut header = data
+        .get(start..start.checked_add(usize::try_from(size).ok()?)?)?
+        .to_vec();
+    for _ in 0..MAX_HEADER_DEPTH {
+        let mut reader = Reader {
+            data: &header,
+            position: 0,
+        };
+        match reader.number()? {
+            K_HEADER => return read_header(&mut reader),
+            K_ENCODED_HEADER => {
+                let streams = read_streams_info(&mut reader)?;
+                let folder = streams.folders.first()?;
+                if folder.coders.iter().any(|coder| coder.id == CODER_AES) {
+                    return Some(Listing {
+                        members: vec![],
+                        encrypted: true,
+                    });
+                }
+                let pack_start = usize::try_from(
+                    streams
+                        .pack_position
+                        .checked_add(SIGNATURE_HEADER_SIZE as u64)?,
+                )
+                .ok()?;
+                let pack_size = usize::try_from(*streams.pack_sizes.first()?).ok()?;
+                let packed = data.get(pack_start..pack_start.checked_add(pack_size)?)?;
+                header = decode(folder, packed)?;
+            }
+            _ => return None,
+        }
+    }
+    None
+}
+
+/// Decodes a header folder, which 7-Zip compresses with a single LZMA or LZMA2 coder.
+fn decode(folder: &Folder, packed: &[u8]) -> Option<Vec<u8>> {
+    let [coder] = folder.coders.as_slice() else {
+        return None;
+    };
+    let size = folder
+        .unpack_size()
+        .filter(|&size| size <= MAX_HEADER_SIZE)?;
+    let mut out = Vec::with_capacity(size as usize);
+    match coder.id.as_slice() {
+        CODER_COPY => out.extend_from_slice(packed.get(..size as usize)?),
+        CODER_LZMA => {
+            // the coder properties are the `lc`, `lp` and `pb` byte and the dictionary size
+            let mut input = BufReader::new(coder.properties.as_slice().chain(packed));
+            lzma_rs::lzma_decompress_with_options(
+                &mut input,
+                &mut out,
+                &Options {
+                    unpacked_size: UnpackedSize::UseProvided(Some(size)),
+                    memlimit: None,
+                    allow_incomplete: false,
+                },
+            )
+            .ok()?;
+        }
+        CODER_LZMA2 => {
+            // lzma-rs does not bound the output of LZMA2, the chunk headers do
+            if lzma2_unpacked_size(packed)? > size {
+                return None;
+            }
+            lzma_rs::lzma2_decompress(&mut BufReader::new(packed), &mut out).ok()?
+        }
+        _ => return None,
+    }
+    Some(out)
+}
+
+/// Size an LZMA2 stream decompresses to, the sum of the sizes in its chunk headers.
+fn lzma2_unpacked_size(packed: &[u8]) -> Option<u64> {
+    let mut position = 0;
+    let mut total = 0u64;
+    loop {
+        let control = *packed.get(position)?;
+        let (unpacked, next) = match control {
+            0 => return Some(total),
+            // uncompressed chunk: 16-bit size - 1 and the data
+            1 | 2 => {
+                let size = read_uint(packed, position + 1, 2, false)? + 1;
+                (size, position + 3 + size as usize)
+            }
+            // LZMA chunk: 21-bit unpacked size - 1, 16-bit packed size - 1, and the
+            // properties byte when bits 5 and 6 ask for new properties
+            0x80.. => {
+                let low = read_uint(packed, position + 1, 2, false)?;
+                let size = ((control as u64 & 0x1f) << 16 | low) + 1;
+                let packed_size = read_uint(packed, position + 3, 2, false)? + 1;
+                let properties = ((control >> 5) & 3 >= 2) as usize;
+                (size, position + 5 + properties + packed_size as usize)
+            }
+            _ => return None,
+        };
+        total += unpacked;
+        position = next;
+    }
+}
+
+struct Coder {
+    id: Vec<u8>,
+    out_streams: u64,
+    properties: Vec<u8>,
+}
+
+#[derive(Default)]
+struct Folder {
+    coders: Vec<Coder>,
+    /// Output streams consumed by another coder of the folder
+    bound_outputs: Vec<u64>,
+    packed_streams: usize,
+    unpack_sizes: Vec<u64>,
+    crc_defined: bool,
+}
+
+impl Folder {
+    /// Size of the output stream no other coder consumes.
+    fn unpack_size(&self) -> Option<u64> {
+        (0..self.unpack_sizes.len() as u64)
+            .find(|index| !self.bound_outputs.contains(index))
+            .and_then(|index| self.unpack_sizes.get(index as usize).copied())
+    }
+
+    /// Coder names in the order 7-Zip lists them, e.g. `BCJ LZMA2`.
+    fn method(&self) -> String {
+        self.coders
+            .iter()
+            .map(|coder| coder_name(&coder.id))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+#[derive(Default)]
+struct StreamsInfo {
+    pack_position: u64,
+    pack_sizes: Vec<u64>,
+    folders: Vec<Folder>,
+    /// Sizes of the files in each folder
+    substreams: Vec<Vec<u64>>,
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl Reader<'_> {
+    fn byte(&mut self) -> Option<u8> {
+        let byte = *self.data.get(self.position)?;
+        self.position += 1;
+        Some(byte)
+    }
+
+    fn bytes(&mut self, count: u64) -> Option<&[u8]> {
+        let end = self.position.checked_add(usize::try_from(count).ok()?)?;
+        let bytes = self.data.get(self.position..end)?;
+        self.position = end;
+        Some(bytes)
+    }
+
+    /// The number of leading one bits of the first byte is the count of bytes that follow,
+    /// the remaining bits of the first byte are the most significant ones.
+    fn number(&mut self) -> Option<u64> {
+        let first = self.byte()?;
+        let mut value = 0u64;
+        for index in 0..8 {
+            let mask = 0x80u8 >> index;
+            if first & mask == 0 {
+                let high = (first & mask.wrapping_sub(1)) as u64;
+                return Some(value | high.checked_shl(8 * index).unwrap_or_default());
+            }
+            value |= (self.byte()? as u64) << (8 * index);
+        }
+        Some(value)
+    }
+
+    /// A number of items that take at least a byte each.
+    fn count(&mut self) -> Option<usize> {
+        let count = usize::try_from(self.number()?).ok()?;
+        (count <= self.data.len() - self.position.min(self.data.len())).then_some(count)
+    }
+
+    /// Most significant bit first.
+    fn bits(&mut self, count: usize) -> Option<Vec<bool>> {
+        let bytes = self.bytes(count.div_ceil(8) as u64)?;
+        Some(
+            (0..count)
+                .map(|index| bytes[index / 8] & (0x80 >> (index % 8)) != 0)
+                .collect(),
+        )
+    }
+
+    /// Returns which of the digests are defined.
+    fn digests(&mut self, count: usize) -> Option<Vec<bool>> {
+        let defined = match self.byte()? {
+            0 => self.bits(count)?,
+            _ => vec![true; count],
+        };
+        let present = defined.iter().filter(|&&defined| defined).count();
+        self.bytes(present as u64 * 4)?;
+        Some(defined)
+    }
+}
+
+fn read_header(reader: &mut Reader) -> Option<Listing> {
+    let mut streams = StreamsInfo::default();
+    let mut members = vec![];
+    loop {
+        match reader.number()? {
+            K_END => break,
+            K_ARCHIVE_PROPERTIES => loop {
+                if reader.number()? == K_END {
+                    break;
+                }
+                let size = reader.number()?;
+                reader.bytes(size)?;
+            },
+            K_ADDITIONAL_STREAMS_INFO => {
+                read_streams_info(reader)?;
+            }
+            K_MAIN_STREAMS_INFO => streams = read_streams_info(reader)?,
+            K_FILES_INFO => members = read_files_info(reader, &streams)?,
+            _ => return None,
+        }
+    }
+    Some(Listing {
+        members,
+        encrypted: false,
+    })
+}
+
+fn read_streams_info(reader: &mut Reader) -> Option<StreamsInfo> {
+    let mut streams = StreamsInfo::default();
+    loop {
+        match reader.number()? {
+            K_END => break,
+            K_PACK_INFO => {
+                streams.pack_position = reader.number()?;
+                let count = reader.count()?;
+                loop {
+                    match reader.number()? {
+                        K_END => break,
+                        K_SIZE => {
+                            streams.pack_sizes =
+                                (0..count).map(|_| reader.number()).collect::<Option<_>>()?
+                        }
+                        K_CRC => {
+                            reader.digests(count)?;
+                        }
+                        _ => return None,
+                    }
+                }
+            }
+            K_UNPACK_INFO => streams.folders = read_unpack_info(reader)?,
+            K_SUBSTREAMS_INFO => streams.substreams = read_substreams_info(reader, &streams)?,
+            _ => return None,
+        }
+    }
+    // folders without substreams information hold a single file
+    if streams.substreams.is_empty() {
+        streams.substreams = streams
+            .folders
+            .iter()
+            .map(|folder| folder.unpack_size().into_iter().collect())
+            .collect();
+    }
+    Some(streams)
+}
+
+fn read_unpack_info(reader: &mut Reader) -> Option<Vec<Folder>> {
+    if reader.number()? != K_FOLDER {
+        return None;
+    }
+    let count = reader.count()?;
+    // folders stored in another stream are not used by 7-Zip
+    if reader.byte()? != 0 {
+        return None;
+    }
+    let mut folders = (0..count)
+        .map(|_| read_folder(reader))
+        .collect::<Option<Vec<_>>>()?;
+    if reader.number()? != K_CODERS_UNPACK_SIZE {
+        return None;
+    }
+    for folder in folders.iter_mut() {
+        let outputs = folder.coders.iter().try_fold(0u64, |outputs, coder| {
+            outputs.checked_add(coder.out_streams)
+        })?;
+        folder.unpack_sizes = (0..outputs)
+            .map(|_| reader.number())
+            .collect::<Option<_>>()?;
+    }
+    loop {
+        match reader.number()? {
+            K_END => break,
+            K_CRC => {
+                let defined = reader.digests(count)?;
+                for (folder, defined) in folders.iter_mut().zip(defined) {
+                    folder.crc_defined = defined;
+                }
+            }
+            _ => return None,
+        }
+    }
+    Some(folders)
+}
+
+fn read_folder(reader: &mut Reader) -> Option<Folder> {
+    let mut folder = Folder::default();
+    let coders = reader.count()?;
+    let (mut inputs, mut outputs) = (0u64, 0u64);
+    for _ in 0..coders {
+        // id size in bits 0 to 3, complex coder in bit 4, properties in bit 5
+        let flags = reader.byte()?;
+        if flags & 0x80 != 0 {
+            return None;
+        }
+        let id = reader.bytes((flags & 0x0f) as u64)?.to_vec();
+        let (in_streams, out_streams) = match flags & 0x10 {
+            0 => (1, 1),
+            _ => (reader.number()?, reader.number()?),
+        };
+        let properties = match flags & 0x20 {
+            0 => vec![],
+            _ => {
+                let size = reader.number()?;
+                reader.bytes(size)?.to_vec()
+            }
+        };
+        inputs = inputs.checked_add(in_streams)?;
+        outputs = outputs.checked_add(out_streams)?;
+        folder.coders.push(Coder {
+            id,
+            out_streams,
+            properties,
+        });
+    }
+    let bind_pairs = outputs.checked_sub(1)?;
+    for _ in 0..bind_pairs {
+        reader.number()?;
+        folder.bound_outputs.push(reader.number()?);
+    }
+    folder.packed_streams = usize::try_from(inputs.checked_sub(bind_pairs)?).ok()?;
+    if folder.packed_streams > 1 {
+        for _ in 0..folder.packed_streams {
+            reader.number()?;
+        }
+    }
+    Some(folder)
+}
+
+fn read_substreams_info(reader: &mut Reader, streams: &StreamsInfo) -> Option<Vec<Vec<u64>>> {
+    let mut counts = vec![1; streams.folders.len()];
+    let mut sizes: Option<Vec<Vec<u64>>> = None;
+    let mut kind = reader.number()?;
+    if kind == K_NUM_UNPACK_STREAM {
+        for count in counts.iter_mut() {
+            *count = reader.count()?;
+        }
+        kind = reader.number()?;
+    }
+    if kind == K_SIZE {
+        let mut all = vec![];
+        for (folder, &count) in streams.folders.iter().zip(&counts) {
+            if count == 0 {
+                all.push(vec![]);
+                continue;
+            }
+            let mut folder_sizes = (1..count)
+                .map(|_| reader.number())
+                .collect::<Option<Vec<_>>>()?;
+            let sum = folder_sizes
+                .iter()
+                .try_fold(0u64, |sum, &size| sum.checked_add(size))?;
+            folder_sizes.push(folder.unpack_size()?.checked_sub(sum)?);
+            all.push(folder_sizes);
+        }
+        sizes = Some(all);
+        kind = reader.number()?;
+    }
+    loop {
+        match kind {
+            K_END => break,
+            K_CRC => {
+                // digests of the files whose folder digest does not already cover them
+                let count = streams
+                    .folders
+                    .iter()
+                    .zip(&counts)
+                    .map(|(folder, &count)| match count == 1 && folder.crc_defined {
+                        true => 0,
+                        false => count,
+                    })
+                    .sum();
+                reader.digests(count)?;
+            }
+            _ => return None,
+        }
+        kind = reader.number()?;
+    }
+    Some(sizes.unwrap_or_else(|| {
+        streams
+            .folders
+            .iter()
+            .zip(&counts)
+            .map(|(folder, &count)| match count {
+                0 => vec![],
+                _ => folder.unpack_size().into_iter().collect(),
+            })
+            .collect()
+    }))
+}
+
+fn read_files_info(reader: &mut Reader, streams: &StreamsInfo) -> Option<Vec<ArchiveMember>> {
+    let count = reader.count()?;
+    let mut empty_streams = vec![false; count];
+    let mut empty_files = vec![];
+    let mut names = vec![];
+    loop {
+        let kind = reader.number()?;
+        if kind == K_END {
+            break;
+        }
+        let size = reader.number()?;
+        let property = reader.bytes(size)?;
+        let mut property = Reader {
+            data: property,
+            position: 0,
+        };
+        match kind {
+            K_EMPTY_STREAM => empty_streams = property.bits(count)?,
+            K_EMPTY_FILE => {
+                let empty = empty_streams.iter().filter(|&&empty| empty).count();
+                empty_files = property.bits(empty)?;
+            }
+            K_NAME => {
+                // names in another stream are not used by 7-Zip
+                if property.byte()? != 0 {
+                    return None;
+                }
+                let units = property.data[1..]
+                    .chunks_exact(2)
+                    .map(|unit| u16::from_le_bytes([unit[0], unit[1]]))
+                    .collect::<Vec<_>>();
+                names = units
+                    .split(|&unit| unit == 0)
+                    .take(count)
+                    .map(String::from_utf16_lossy)
+                    .collect();
+            }
+            _ => {}
+        }
+    }
+    // files with data take the substreams in order
+    let packed_sizes = folder_packed_sizes(streams);
+    let mut substreams = streams
+        .folders
+        .iter()
+        .zip(&streams.substreams)
+        .zip(packed_sizes)
+        .flat_map(|((folder, sizes), packed_size)| {
+            let packed_size = packed_size.filter(|_| sizes.len() == 1);
+            sizes
+                .iter()
+                .map(move |&size| (size, packed_size, folder.method()))
+        });
+    let mut empty_index = 0;
+    let mut members = vec![];
+    for (index, empty) in empty_streams.into_iter().enumerate() {
+        let name = names.get(index).cloned().unwrap_or_default();
+        if empty {
+            let directory = !empty_files.get(empty_index).copied().unwrap_or_default();
+            empty_index += 1;
+            if !directory {
+                members.push(ArchiveMember {
+                    name,
+                    size: 0,
+                    packed_size: Some(0),
+                    compression: "stored".to_string(),
+                });
+            }
+            continue;
+        }
+        let (size, packed_size, compression) = substreams.next()?;
+        members.push(ArchiveMember {
+            name,
+            size,
+            packed_size,
+            compression,
+        });
+    }
+    Some(members)
+}
+
+/// Sums the packed streams of each folder, which are stored in folder order.
+fn folder_packed_sizes(streams: &StreamsInfo) -> Vec<Option<u64>> {
+    let mut first = 0usize;
+    streams
+        .folders
+        .iter()
+        .map(|folder| {
+            let end = first.saturating_add(folder.packed_streams);
+            let sizes = streams.pack_sizes.get(first..end);
+            first = end;
+            sizes.and_then(|sizes| {
+                sizes
+                    .iter()
+                    .try_fold(0u64, |sum, &size| sum.checked_add(size))
+            })
+        })
+        .collect()
+}
+
+fn coder_name(id: &[u8]) -> String {
+    match id {
+        CODER_COPY => "Copy".to_string(),
+        [0x03] => "Delta".to_string(),
+        [0x03, 0x03, 0x01, 0x03] => "BCJ".to_string(),
+        [0x03, 0x03, 0x01, 0x1b] => "BCJ2".to_string(),
+        [0x03, 0x03, 0x02, 0x05] => "PPC".to_string(),
+        [0x03, 0x03, 0x04, 0x01] => "IA64".to_string(),
+        [0x03, 0x03, 0x05, 0x01] => "ARM".to_string(),
+        [0x03, 0x03, 0x08, 0x05] => "SPARC".to_string(),
+        [0x0a] => "ARM64".to_string(),
+        CODER_LZMA => "LZMA".to_string(),
+        CODER_LZMA2 => "LZMA2".to_string(),
+        [0x03, 0x04, 0x01] => "PPMD".to_string(),
+        [0x04, 0x01, 0x08] => "Deflate".to_string(),
+        [0x04, 0x01, 0x09] => "Deflate64".to_string(),
+        [0x04, 0x02, 0x02] => "BZip2".to_string(),
+        CODER_AES => "AES".to_string(),
+        _ => format!("unknown ({})", hex::encode(id)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn number(data: &[u8]) -> Option<u64> {
+        Reader { data, position: 0 }.number()
+    }
+
+    #[test]
+    fn number_sizes() {
+        assert_eq!(number(&[0x7f]), Some(0x7f));
+        // one extra byte, the high bits of the first byte above it
+        assert_eq!(number(&[0x81, 0x23]), Some(0x123));
+        assert_eq!(number(&[0xc1, 0x23, 0x45]), Some(0x1_4523));
+        assert_eq!(
+            number(&[0xfe, 1, 2, 3, 4, 5, 6, 7]),
+            Some(0x0007_0605_0403_0201)
+        );
+        assert_eq!(
+            number(&[0xff, 1, 2, 3, 4, 5, 6, 7, 8]),
+            Some(0x0807_0605_0403_0201)
+        );
+        assert_eq!(number(&[0xff; 9]), Some(u64::MAX));
+    }
+
+    #[test]
+    fn number_truncated() {
+        assert_eq!(number(&[]), None);
+        let data = [0xff, 1, 2, 3, 4, 5, 6, 7, 8];
+        for end in 1..data.len() {
+            assert_eq!(number(&data[..end]), None);
+        }
+    }
+
+    #[test]
+    fn count_bounded_by_data() {
+        let mut reader = Reader {
+            data: &[3, 0, 0, 0],
+            position: 0,
+        };
+        assert_eq!(reader.count(), Some(3));
+        let mut reader = Reader {
+            data: &[4, 0, 0, 0],
+            position: 0,
+        };
+        assert_eq!(reader.count(), None);
+        let mut reader = Reader {
+            data: &[0xff; 9],
+            position: 0,
+        };
+        assert_eq!(reader.count(), None);
+    }
+
+    #[test]
+    fn lzma2_sizes() {
+        // uncompressed chunk of 3 bytes, LZMA chunk of 0x10000 bytes from 2 with properties
+        let mut packed = vec![1, 0, 2, b'a', b'b', b'c'];
+        packed.extend([0xe0, 0xff, 0xff, 0, 1, 0x5d, 0xaa, 0xbb]);
+        assert_eq!(lzma2_unpacked_size(&packed), None);
+        packed.push(0);
+        assert_eq!(lzma2_unpacked_size(&packed), Some(3 + 0x10000));
+        assert_eq!(lzma2_unpacked_size(&[0x03]), None);
+    }
+
+    #[test]
+    fn hostile_folders() {
+        // one complex coder with two in-streams and one out-stream
+        let folder = [0x01, 0x11, 0x21, 0x02, 0x01, 0x00, 0x01];
+        let mut reader = Reader {
+            data: &folder,
+            position: 0,
+        };
+        let folder = read_folder(&mut reader).unwrap();
+        assert_eq!(folder.packed_streams, 2);
+        let streams = StreamsInfo {
+            pack_sizes: vec![u64::MAX, u64::MAX],
+            folders: vec![folder],
+            ..StreamsInfo::default()
+        };
+        assert_eq!(folder_packed_sizes(&streams), [None]);
+        // in-streams and out-streams that add up past u64::MAX
+        let mut data = vec![0x02];
+        for _ in 0..2 {
+            data.extend([0x11, 0x21]);
+            data.extend([0xff; 18]);
+        }
+        let mut reader = Reader {
+            data: &data,
+            position: 0,
+        };
+        assert!(read_folder(&mut reader).is_none());
+        // a folder whose packed streams start past the end of the pack sizes
+        let streams = StreamsInfo {
+            pack_sizes: vec![1],
+            folders: vec![Folder {
+                packed_streams: usize::MAX,
+                ..Folder::default()
+            }],
+            ..StreamsInfo::default()
+        };
+        assert_eq!(folder_packed_sizes(&streams), [None]);
+    }
+
+    #[test]
+    fn list_truncated() {
+        assert!(list(b"7z\xbc\xaf\x27\x1c\x00\x04").is_none());
+        assert!(list(&[]).is_none());
+    }
+}
@@ -0,0 +1,100 @@
+use super::{ArchiveMember, Listing};
+use crate::util::{le_u16, le_u32, le_u64};
+
+pub const LOCAL_HEADER_MAGIC: &[u8] = b"PK\x03\x04";
+const CENTRAL_HEADER_MAGIC: &[u8] = b"PK\x01\x02";
+const END_OF_CENTRAL_DIRECTORY_MAGIC: &[u8] = b"PK\x05\x06";
+const END_OF_CENTRAL_DIRECTORY_SIZE: usize = 22;
+const CENTRAL_HEADER_SIZE: usize = 46;
+/// The archive comment after the end of central directory record is at most this long
+const MAX_COMMENT_SIZE: usize = 0xffff;
+const ZIP64_EXTRA_FIELD: u16 = 0x0001;
+const FLAG_ENCRYPTED: u16 = 0x0001;
+
+/// Lists the central directory of a ZIP archive that ends `data`.
+///
+/// The central directory is found from the end of central directory record, which also
+/// works for SFX archives whose offsets were not adjusted for the stub.
+pub fn list(data: &[u8]) -> Option<Listing> {
+    let search = data
+        .len()
+        .saturating_sub(END_OF_CENTRAL_DIRECTORY_SIZE + MAX_COMMENT_SIZE);
+    let end = search
+        + data[search..]
+            .windows(END_OF_CENTRAL_DIRECTORY_MAGIC.len())
+            .rposition(|window| window == END_OF_CENTRAL_DIRECTORY_MAGIC)?;
+    let entries = le_u16(data, end + 10)?;
+    let directory_size = le_u32(data, end + 12)? as usize;
+    let mut position = end.checked_sub(directory_size)?;
+    let mut members = vec![];
+    for _ in 0..entries {
+        if data.get(position..position + 4)? != CENTRAL_HEADER_MAGIC {
+            break;
+        }
+        let flags = le_u16(data, position + 8)?;
+        let method = le_u16(data, position + 10)?;
+        let mut packed_size = le_u32(data, position + 20)? as u64;
+        let mut size = le_u32(data, position + 24)? as u64;
+        let name_size = le_u16(data, position + 28)? as usize;
+        let extra_size = le_u16(data, position + 30)? as usize;
+        let comment_size = le_u16(data, position + 32)? as usize;
+        let name_start = position + CENTRAL_HEADER_SIZE;
+        let name = data.get(name_start..name_start + name_size)?;
+        let extra = data.get(name_start + name_size..name_start + name_size + extra_size)?;
+        // ZIP64 sizes replace the saturated ones in order
+        if let Some(zip64) = extra_field(extra, ZIP64_EXTRA_FIELD) {
+            let mut values = zip64.chunks_exact(8).map(|value| le_u64(value, 0));
+            if size == u32::MAX as u64 {
+                size = values.next().flatten()?;
+            }
+            if packed_size == u32::MAX as u64 {
+                packed_size = values.next().flatten()?;
+            }
+        }
+        position = name_start + name_size + extra_size + comment_size;
+        if name.ends_with(b"/") {
+            continue;
+        }
+        let mut compression = method_name(method);
+        if flags & FLAG_ENCRYPTED != 0 {
+            compression.push_str(" (encrypted)");
+        }
+        members.push(ArchiveMember {
+            name: String::from_utf8_lossy(name).to_string(),
+            size,
+            packed_size: Some(packed_size),
+            compression,
+        });
+    }
+    Some(Listing {
+        members,
+        encrypted: false,
+    })
+}
+
+fn extra_field(extra: &[u8], id: u16) -> Option<&[u8]> {
+    let mut position = 0;
+    while let (Some(field), Some(size)) = (le_u16(extra, position), le_u16(extra, position + 2)) {
+        let data = extra.get(position + 4..position + 4 + size as usize)?;
+        if field == id {
+            return Some(data);
+        }
+        position += 4 + size as usize;
+    }
+    None
+}
+
+fn method_name(method: u16) -> String {
+    match method {
+        0 => "stored".to_string(),
+        8 => "Deflate".to_string(),
+        9 => "Deflate64".to_string(),
+        12 => "BZip2".to_string(),
+        14 => "LZMA".to_string(),
+        93 => "Zstandard".to_string(),
+        95 => "XZ".to_string(),
+        98 => "PPMd".to_string(),
+        99 => "AES".to_string(),
+        _ => format!("unknown ({})", method),
+    }
+}
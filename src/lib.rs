@@ -1,23 +1,49 @@
 extern crate core;
 
 use crate::{
-    elf::ElfFileInformation,
+    elf::{
+        debug_info::DebugInfo,
+        dynamic::DynamicSection,
+        hardening::Hardening,
+        init_functions::InitFunctions,
+        notes::Notes,
+        relocations::Relocations,
+        segments::SegmentTable,
+        symbols::{Exports, SymbolTable},
+        toolchain::Toolchain,
+        versions::SymbolVersions,
+        ElfFileInformation, Header,
+    },
     entry_point::EntryPoint,
     error::Error,
+    go::GoInfo,
+    installer::Installer,
+    packers::Packers,
     pe::{
-        imports::Imports, resource::Resources, rich_headers::RichTable,
+        imports::Imports, mitigations::Mitigations, resource::Resources, rich_headers::RichTable,
         signatures::PeAuthenticodes, tls::TlsCallbacks, PeFileInformation,
     },
+    python::PythonInfo,
+    rust::RustInfo,
     sections::SectionTable,
+    upx::UpxImage,
 };
 use serde::{Deserialize, Serialize};
 use std::{fs::read, path::Path};
 
+pub mod database;
 pub mod elf;
 pub mod entry_point;
 pub mod error;
+pub mod go;
+pub mod installer;
+pub mod packers;
 pub mod pe;
+pub mod python;
+pub mod rust;
 pub mod sections;
+pub mod upx;
+pub(crate) mod util;
 
 pub type Result<T> = std::result::Result<T, Error>;
 
@@ -45,6 +71,66 @@ pub struct BinExInfo {
     /// TLS Callbacks (PE only)
     #[serde(default)]
     pub tls_callbacks: Option<TlsCallbacks>,
+    /// Segments (ELF only)
+    #[serde(default)]
+    pub segments: Option<SegmentTable>,
+    /// Dynamic Section (ELF only)
+    #[serde(default)]
+    pub dynamic: Option<DynamicSection>,
+    /// Exported dynamic symbols (ELF only)
+    #[serde(default)]
+    pub exports: Option<Exports>,
+    /// Static symbol table, if not stripped (ELF only)
+    #[serde(default)]
+    pub symbols: Option<SymbolTable>,
+    /// Notes (ELF only)
+    #[serde(default)]
+    pub notes: Option<Notes>,
+    /// Hardening (ELF only)
+    #[serde(default)]
+    pub hardening: Option<Hardening>,
+    /// Exploit mitigations (PE only)
+    #[serde(default)]
+    pub mitigations: Option<Mitigations>,
+    /// Relocations (ELF only)
+    #[serde(default)]
+    pub relocations: Option<Relocations>,
+    /// Constructors and destructors (ELF only)
+    #[serde(default)]
+    pub init_functions: Option<InitFunctions>,
+    /// ELF Header (ELF only)
+    #[serde(default)]
+    pub elf_header: Option<Header>,
+    /// Symbol version definitions and requirements (ELF only)
+    #[serde(default)]
+    pub versions: Option<SymbolVersions>,
+    /// DWARF and separate debug file links (ELF only)
+    #[serde(default)]
+    pub debug_info: Option<DebugInfo>,
+    /// Compiler, linker and libc (ELF only)
+    #[serde(default)]
+    pub toolchain: Option<Toolchain>,
+    /// Go build info and pclntab functions (ELF & PE)
+    #[serde(default)]
+    pub go: Option<GoInfo>,
+    /// rustc version and crate dependencies (ELF & PE)
+    #[serde(default)]
+    pub rust: Option<RustInfo>,
+    /// Packers and protectors (ELF & PE)
+    #[serde(default)]
+    pub packers: Option<Packers>,
+    /// Installer or self-extracting archive and its members (ELF & PE)
+    #[serde(default)]
+    pub installer: Option<Installer>,
+    /// PyInstaller, py2exe or Nuitka packaging and its table of contents (ELF & PE)
+    #[serde(default)]
+    pub python: Option<PythonInfo>,
+    /// UPX layer that was decompressed (ELF & PE)
+    #[serde(default)]
+    pub upx: Option<UpxImage>,
+    /// Information for the binary unpacked from the UPX layer (ELF & PE)
+    #[serde(default)]
+    pub unpacked: Option<Box<BinExInfo>>,
 }
 
 impl From<PeFileInformation> for BinExInfo {
@@ -57,6 +143,26 @@ impl From<PeFileInformation> for BinExInfo {
             imports: Some(val.imports),
             resources: val.resources,
             tls_callbacks: Some(val.tls),
+            segments: None,
+            dynamic: None,
+            exports: None,
+            symbols: None,
+            notes: None,
+            hardening: None,
+            mitigations: Some(val.mitigations),
+            relocations: None,
+            init_functions: None,
+            elf_header: None,
+            versions: None,
+            debug_info: None,
+            toolchain: None,
+            go: val.go,
+            rust: val.rust,
+            packers: Some(val.packers),
+            installer: val.installer,
+            python: val.python,
+            upx: None,
+            unpacked: None,
         }
     }
 }
@@ -71,6 +177,26 @@ impl From<ElfFileInformation> for BinExInfo {
             imports: Some(val.imports),
             resources: None,
             tls_callbacks: None,
+            segments: Some(val.segments),
+            dynamic: Some(val.dynamic),
+            exports: Some(val.exports),
+            symbols: val.symbols,
+            notes: Some(val.notes),
+            hardening: Some(val.hardening),
+            mitigations: None,
+            relocations: Some(val.relocations),
+            init_functions: Some(val.init_functions),
+            elf_header: Some(val.header),
+            versions: Some(val.versions),
+            debug_info: Some(val.debug_info),
+            toolchain: Some(val.toolchain),
+            go: val.go,
+            rust: val.rust,
+            packers: Some(val.packers),
+            installer: val.installer,
+            python: val.python,
+            upx: None,
+            unpacked: None,
         }
     }
 }
@@ -89,9 +215,37 @@ pub fn get_file_extended_information<P: AsRef<Path>>(file_path: P) -> Result<Bin
         return Err(Error::FileNotFound);
     }
     let payload = read(file_path)?;
-    match goblin::Object::parse(&payload)? {
-        goblin::Object::Elf(elf) => Ok(ElfFileInformation::parse(&elf)?.into()),
-        goblin::Object::PE(pe) => Ok(PeFileInformation::parse((&pe, &payload))?.into()),
-        _ => Err(Error::UnsupportedFileType),
+    get_extended_information(&payload)
+}
+
+/// Get the Extended Information for a binary already read into memory
+///
+/// UPX packed binaries are decompressed and the unpacked binary is reported in `unpacked`.
+pub fn get_extended_information(payload: &[u8]) -> Result<BinExInfo> {
+    extended_information(payload, 0)
+}
+
+/// Packed layers are unpacked up to this depth, a binary that unpacks to another packed one
+/// is not followed further.
+const MAX_UNPACK_DEPTH: usize = 1;
+
+fn extended_information(payload: &[u8], depth: usize) -> Result<BinExInfo> {
+    let mut info: BinExInfo = match goblin::Object::parse(payload)? {
+        goblin::Object::Elf(elf) => ElfFileInformation::parse((&elf, payload))?.into(),
+        goblin::Object::PE(pe) => PeFileInformation::parse((&pe, payload))?.into(),
+        _ => return Err(Error::UnsupportedFileType),
+    };
+    let upx = info
+        .packers
+        .as_ref()
+        .is_some_and(|packers| packers.detections.iter().any(|d| d.name == "UPX"));
+    if upx && depth < MAX_UNPACK_DEPTH {
+        if let Ok(image) = UpxImage::unpack(payload) {
+            info.unpacked = extended_information(&image.data, depth + 1)
+                .ok()
+                .map(Box::new);
+            info.upx = Some(image);
+        }
     }
+    Ok(info)
 }
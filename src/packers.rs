@@ -0,0 +1,410 @@
+use crate::{
+    database::SignatureDatabase,
+    elf::segments::{vaddr_to_offset, SegmentTable},
+    entry_point::BytePattern,
+    pe::{imports::Imports, util::rva_to_offset},
+    sections::SectionTable,
+    util::safe_read,
+};
+use goblin::{
+    elf::{program_header::PT_LOAD, Elf},
+    pe::{
+        section_table::{IMAGE_SCN_MEM_EXECUTE, IMAGE_SCN_MEM_WRITE},
+        PE,
+    },
+};
+use serde::{Deserialize, Serialize};
+use std::{fmt, sync::RwLock};
+
+const PACKERS: &str = include_str!("../assets/packers.txt");
+
+/// How much of the file `header` signatures look at.
+const HEADER_SIZE: usize = 0x1000;
+/// Sections above this entropy hold compressed or encrypted data.
+const HIGH_ENTROPY: f32 = 7.2;
+/// Fewer imports than this are typical for a stub that resolves the rest itself.
+const FEW_IMPORTS: usize = 10;
+/// Heuristics that must fire to report a file as packed without a signature match.
+const PACKED_HEURISTICS: usize = 2;
+
+lazy_static::lazy_static! {
+    /// Loaded packer databases, later ones take precedence.
+    static ref PACKER_DATABASES: RwLock<Vec<PackerDatabase>> =
+        RwLock::new(vec![PackerDatabase::builtin()]);
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub enum SignatureKind {
+    #[default]
+    Section,
+    EntryPoint,
+    Overlay,
+    Header,
+    String,
+}
+
+impl SignatureKind {
+    /// Confidence a single match of this kind gives.
+    fn confidence(self) -> u8 {
+        match self {
+            SignatureKind::EntryPoint => 90,
+            SignatureKind::Header => 85,
+            SignatureKind::Overlay => 80,
+            SignatureKind::String => 70,
+            SignatureKind::Section => 60,
+        }
+    }
+}
+
+impl fmt::Display for SignatureKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let kind = match self {
+            SignatureKind::Section => "section",
+            SignatureKind::EntryPoint => "entrypoint",
+            SignatureKind::Overlay => "overlay",
+            SignatureKind::Header => "header",
+            SignatureKind::String => "string",
+        };
+        write!(f, "{}", kind)
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PackerSignature {
+    pub name: String,
+    /// e.g. `packer` or `protector`
+    pub category: String,
+    pub kind: SignatureKind,
+    pub pattern: BytePattern,
+    /// The pattern as written in the database
+    pub text: String,
+}
+
+impl PackerSignature {
+    fn parse(line: &str) -> Option<PackerSignature> {
+        let fields: Vec<&str> = line.splitn(4, '|').map(str::trim).collect();
+        let [name, category, kind, text] = fields[..] else {
+            return None;
+        };
+        let kind = match kind {
+            "section" => SignatureKind::Section,
+            "entrypoint" => SignatureKind::EntryPoint,
+            "overlay" => SignatureKind::Overlay,
+            "header" => SignatureKind::Header,
+            "string" => SignatureKind::String,
+            _ => return None,
+        };
+        let pattern = if kind == SignatureKind::Section {
+            BytePattern::from(text.as_bytes())
+        } else if let Some(string) = text.strip_prefix('"').and_then(|t| t.strip_suffix('"')) {
+            BytePattern::from(string.as_bytes())
+        } else {
+            BytePattern::parse(text)?
+        };
+        if pattern.is_empty() {
+            return None;
+        }
+        Some(PackerSignature {
+            name: name.to_string(),
+            category: category.to_string(),
+            kind,
+            pattern,
+            text: text.to_string(),
+        })
+    }
+}
+
+/// A packer signature database, in the `<name> | <category> | <kind> | <pattern>` format of
+/// `assets/packers.txt`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PackerDatabase {
+    pub version: String,
+    pub signatures: Vec<PackerSignature>,
+}
+
+impl SignatureDatabase for PackerDatabase {
+    const BUILTIN: &'static str = PACKERS;
+    const COMMENT: char = '#';
+
+    fn parse(version: &str, data: &str) -> PackerDatabase {
+        PackerDatabase {
+            version: version.to_string(),
+            signatures: data
+                .lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty() && !line.starts_with('#'))
+                .filter_map(PackerSignature::parse)
+                .collect(),
+        }
+    }
+
+    fn databases() -> &'static RwLock<Vec<PackerDatabase>> {
+        &PACKER_DATABASES
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct PackerDetection {
+    pub name: String,
+    pub category: String,
+    /// 0 to 100, the strongest match plus a bonus for each further one
+    pub confidence: u8,
+    /// Matched signatures, e.g. `section UPX0`
+    pub evidence: Vec<String>,
+    /// Version of the database with the first matching signature
+    pub database: String,
+}
+
+/// Data appended after the end of the image.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Overlay {
+    pub offset: u64,
+    pub size: u64,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Packers {
+    pub detections: Vec<PackerDetection>,
+    /// Packing anomalies independent of a specific packer, e.g. a high entropy section
+    pub heuristics: Vec<String>,
+    pub overlay: Option<Overlay>,
+    /// A packer was detected or at least two heuristics fired, not counting the one on the
+    /// number of imports since plenty of small programs import little
+    pub packed: bool,
+}
+
+/// What the signatures are matched against, the same for PE and ELF.
+struct Target<'a> {
+    data: &'a [u8],
+    section_names: Vec<String>,
+    entry_point: Option<usize>,
+    overlay: Option<Overlay>,
+}
+
+impl Packers {
+    pub fn from_pe(pe: (&PE, &[u8]), sections: &SectionTable, imports: &Imports) -> Packers {
+        let end_of_image =
+            pe.0.sections
+                .iter()
+                .map(|sec| sec.pointer_to_raw_data as u64 + sec.size_of_raw_data as u64)
+                .max()
+                .unwrap_or_default();
+        // the Authenticode signature is appended too, but is not an overlay
+        let certificates =
+            pe.0.header
+                .optional_header
+                .and_then(|oh| oh.data_directories.get_certificate_table().copied())
+                .map(|dd| (dd.virtual_address as u64, dd.size as u64));
+        let end_of_file = match certificates {
+            Some((offset, size))
+                if offset >= end_of_image && offset + size >= pe.1.len() as u64 =>
+            {
+                offset
+            }
+            _ => pe.1.len() as u64,
+        };
+        let target = Target {
+            data: pe.1,
+            section_names: sections.sections.iter().map(|s| s.name.clone()).collect(),
+            entry_point: rva_to_offset(pe.0, pe.0.entry as u32),
+            overlay: overlay(end_of_image, end_of_file),
+        };
+        let mut result = Packers::matching(&target);
+
+        let entry_section = pe.0.sections.iter().position(|sec| {
+            let entry = pe.0.entry as u32;
+            entry >= sec.virtual_address
+                && entry - sec.virtual_address < sec.virtual_size.max(sec.size_of_raw_data)
+        });
+        match entry_section {
+            None if pe.0.entry != 0 => result
+                .heuristics
+                .push("entry point is outside of every section".to_string()),
+            Some(idx) => {
+                let sec = &pe.0.sections[idx];
+                let name = sec.name().unwrap_or_default();
+                if sec.characteristics & IMAGE_SCN_MEM_WRITE != 0 {
+                    result
+                        .heuristics
+                        .push(format!("entry point is in the writable section {}", name));
+                }
+                if idx > 0 && idx + 1 == pe.0.sections.len() {
+                    result
+                        .heuristics
+                        .push(format!("entry point is in the last section {}", name));
+                }
+            }
+            None => {}
+        }
+        for sec in pe.0.sections.iter() {
+            if sec.size_of_raw_data == 0
+                && sec.virtual_size > 0
+                && sec.characteristics & IMAGE_SCN_MEM_EXECUTE != 0
+            {
+                result.heuristics.push(format!(
+                    "executable section {} is empty on disk but {:#x} bytes in memory",
+                    sec.name().unwrap_or_default(),
+                    sec.virtual_size
+                ));
+            }
+        }
+        for section in sections.sections.iter() {
+            let entropy = section.entropy.unwrap_or_default();
+            if entropy > HIGH_ENTROPY && section.raw_size >= 0x200 {
+                result.heuristics.push(format!(
+                    "section {} has a high entropy of {:.2}",
+                    section.name, entropy
+                ));
+            }
+        }
+        result.packed =
+            !result.detections.is_empty() || result.heuristics.len() >= PACKED_HEURISTICS;
+        let import_count: usize = imports.modules.iter().map(|m| m.imports.len()).sum();
+        let managed =
+            pe.0.header
+                .optional_header
+                .is_some_and(|oh| oh.data_directories.get_clr_runtime_header().is_some());
+        if !managed && import_count < FEW_IMPORTS {
+            result
+                .heuristics
+                .push(format!("only {} imported functions", import_count));
+        }
+        result
+    }
+
+    pub fn from_elf(elf: (&Elf, &[u8]), segments: &SegmentTable) -> Packers {
+        let end_of_image = elf
+            .0
+            .program_headers
+            .iter()
+            .map(|ph| ph.p_offset.saturating_add(ph.p_filesz))
+            .chain(
+                elf.0
+                    .section_headers
+                    .iter()
+                    .filter(|sh| sh.sh_type != goblin::elf::section_header::SHT_NOBITS)
+                    .map(|sh| sh.sh_offset.saturating_add(sh.sh_size)),
+            )
+            .chain(std::iter::once(elf.0.header.e_shoff.saturating_add(
+                (elf.0.header.e_shnum as u64).saturating_mul(elf.0.header.e_shentsize as u64),
+            )))
+            .max()
+            .unwrap_or_default();
+        let target = Target {
+            data: elf.1,
+            section_names: elf
+                .0
+                .section_headers
+                .iter()
+                .filter_map(|sh| elf.0.shdr_strtab.get_at(sh.sh_name))
+                .map(|name| name.to_string())
+                .collect(),
+            entry_point: vaddr_to_offset(elf.0, elf.0.entry),
+            overlay: overlay(end_of_image, elf.1.len() as u64),
+        };
+        let mut result = Packers::matching(&target);
+
+        let loads = elf
+            .0
+            .program_headers
+            .iter()
+            .filter(|ph| ph.p_type == PT_LOAD)
+            .count();
+        if elf.0.section_headers.is_empty() && loads > 0 {
+            result.heuristics.push("no section headers".to_string());
+        }
+        for segment in segments.segments.iter().filter(|s| s.r#type == "PT_LOAD") {
+            if segment.flags.contains('W') && segment.flags.contains('X') {
+                result.heuristics.push(format!(
+                    "PT_LOAD at {:#x} is writable and executable",
+                    segment.virt_addr
+                ));
+            }
+            let entropy = segment.entropy.unwrap_or_default();
+            if entropy > HIGH_ENTROPY && segment.file_size >= 0x200 {
+                result.heuristics.push(format!(
+                    "PT_LOAD at {:#x} has a high entropy of {:.2}",
+                    segment.virt_addr, entropy
+                ));
+            }
+        }
+        result.packed =
+            !result.detections.is_empty() || result.heuristics.len() >= PACKED_HEURISTICS;
+        result
+    }
+
+    /// Matches the signatures of every loaded database.
+    fn matching(target: &Target) -> Packers {
+        let mut result = Packers {
+            overlay: target.overlay.clone(),
+            ..Default::default()
+        };
+        let databases = PackerDatabase::loaded();
+        let header = safe_read(target.data, 0, HEADER_SIZE);
+        let entry_point = target
+            .entry_point
+            .map(|offset| safe_read(target.data, offset, 0x100))
+            .unwrap_or_default();
+        let overlay = target
+            .overlay
+            .as_ref()
+            .map(|o| safe_read(target.data, o.offset as usize, o.size as usize))
+            .unwrap_or_default();
+        for database in databases.iter().rev() {
+            for signature in database.signatures.iter() {
+                let matched = match signature.kind {
+                    SignatureKind::Section => target
+                        .section_names
+                        .iter()
+                        .any(|name| name == &signature.text),
+                    SignatureKind::EntryPoint => signature.pattern.matches_at(entry_point),
+                    SignatureKind::Overlay => signature.pattern.matches_at(overlay),
+                    SignatureKind::Header => signature.pattern.find_in(header).is_some(),
+                    SignatureKind::String => signature.pattern.find_in(target.data).is_some(),
+                };
+                if matched {
+                    result.push(database, signature);
+                }
+            }
+        }
+        result
+            .detections
+            .sort_by_key(|d| std::cmp::Reverse(d.confidence));
+        result
+    }
+
+    fn push(&mut self, database: &PackerDatabase, signature: &PackerSignature) {
+        let evidence = format!("{} {}", signature.kind, signature.text);
+        match self
+            .detections
+            .iter_mut()
+            .find(|d| d.name == signature.name)
+        {
+            Some(detection) => {
+                if detection.evidence.contains(&evidence) {
+                    return;
+                }
+                detection.evidence.push(evidence);
+                detection.confidence = detection
+                    .confidence
+                    .max(signature.kind.confidence())
+                    .saturating_add(5)
+                    .min(100);
+            }
+            None => self.detections.push(PackerDetection {
+                name: signature.name.clone(),
+                category: signature.category.clone(),
+                confidence: signature.kind.confidence(),
+                evidence: vec![evidence],
+                database: database.version.clone(),
+            }),
+        }
+    }
+}
+
+fn overlay(end_of_image: u64, end_of_file: u64) -> Option<Overlay> {
+    (end_of_file > end_of_image).then(|| Overlay {
+        offset: end_of_image,
+        size: end_of_file - end_of_image,
+    })
+}
@@ -13,6 +13,12 @@ pub struct Imports {
 pub struct ImportFunction {
     pub name: String,
     pub import_by_ordinal: bool,
+    /// Required symbol version, e.g. `GLIBC_2.17` (ELF only)
+    #[serde(default)]
+    pub version: Option<String>,
+    /// Symbol type, e.g. `FUNC` or `OBJECT` (ELF only)
+    #[serde(default)]
+    pub symbol_type: Option<String>,
 }
 
 #[derive(Clone, Debug, Default, Serialize, Deserialize)]
@@ -33,11 +39,13 @@ impl Imports {
                         ImportFunction {
                             name: format!("Ordinal({})", import.ordinal),
                             import_by_ordinal: true,
+                            ..Default::default()
                         }
                     } else {
                         ImportFunction {
                             name: import.name.to_string(),
                             import_by_ordinal: false,
+                            ..Default::default()
                         }
                     });
                     acc
@@ -0,0 +1,285 @@
+use crate::{
+    pe::util::rva_to_offset,
+    util::{le_u32, read_uint, safe_read},
+    Result,
+};
+use goblin::pe::{
+    characteristic::IMAGE_FILE_RELOCS_STRIPPED,
+    dll_characteristic::{
+        IMAGE_DLLCHARACTERISTICS_DYNAMIC_BASE, IMAGE_DLLCHARACTERISTICS_FORCE_INTEGRITY,
+        IMAGE_DLLCHARACTERISTICS_GUARD_CF, IMAGE_DLLCHARACTERISTICS_HIGH_ENTROPY_VA,
+        IMAGE_DLLCHARACTERISTICS_NO_SEH, IMAGE_DLLCHARACTERISTICS_NX_COMPAT,
+    },
+    header::COFF_MACHINE_X86,
+    section_table::{IMAGE_SCN_MEM_EXECUTE, IMAGE_SCN_MEM_WRITE},
+    PE,
+};
+use serde::{Deserialize, Serialize};
+
+const IMAGE_DEBUG_TYPE_EX_DLLCHARACTERISTICS: u32 = 20;
+const IMAGE_DLLCHARACTERISTICS_EX_CET_COMPAT: u32 = 0x01;
+const IMAGE_GUARD_CF_INSTRUMENTED: u32 = 0x100;
+const SIZEOF_DEBUG_DIRECTORY: usize = 28;
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub enum CheckStatus {
+    Pass,
+    Fail,
+    #[default]
+    NotApplicable,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct MitigationCheck {
+    pub name: String,
+    pub status: CheckStatus,
+    pub reason: String,
+}
+
+/// Exploit mitigations of a PE image, the same checks as BinSkim.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Mitigations {
+    pub checks: Vec<MitigationCheck>,
+}
+
+/// The fields of `IMAGE_LOAD_CONFIG_DIRECTORY` we need.
+#[derive(Default)]
+struct LoadConfig {
+    security_cookie: Option<u64>,
+    se_handler_count: Option<u64>,
+    guard_cf_function_table: Option<u64>,
+    guard_flags: Option<u32>,
+}
+
+impl LoadConfig {
+    fn parse(pe: (&PE, &[u8])) -> Option<LoadConfig> {
+        let dd =
+            pe.0.header
+                .optional_header?
+                .data_directories
+                .get_load_config_table()
+                .copied()?;
+        let data = safe_read(
+            pe.1,
+            rva_to_offset(pe.0, dd.virtual_address)?,
+            dd.size as usize,
+        );
+        // the directory grew over time, only trust fields inside its own `Size`
+        let size = le_u32(data, 0)? as usize;
+        let data = &data[..size.min(data.len())];
+        let ptr = if pe.0.is_64 { 8 } else { 4 };
+        let (cookie, se_count, cf_table, flags) = if pe.0.is_64 {
+            (0x58, 0x68, 0x80, 0x90)
+        } else {
+            (0x3c, 0x44, 0x50, 0x58)
+        };
+        Some(LoadConfig {
+            security_cookie: read_uint(data, cookie, ptr, true),
+            se_handler_count: read_uint(data, se_count, ptr, true),
+            guard_cf_function_table: read_uint(data, cf_table, ptr, true),
+            guard_flags: le_u32(data, flags),
+        })
+    }
+}
+
+impl Mitigations {
+    pub fn parse(pe: (&PE, &[u8])) -> Result<Mitigations> {
+        let mut result = Mitigations { checks: vec![] };
+        let Some(optional_header) = pe.0.header.optional_header else {
+            return Ok(result);
+        };
+        let flags = optional_header.windows_fields.dll_characteristics;
+        let dirs = &optional_header.data_directories;
+        let load_config = LoadConfig::parse(pe);
+        let managed = dirs.get_clr_runtime_header().is_some();
+        let x86 = pe.0.header.coff_header.machine == COFF_MACHINE_X86;
+
+        let relocs = dirs.get_base_relocation_table().is_some()
+            && pe.0.header.coff_header.characteristics & IMAGE_FILE_RELOCS_STRIPPED == 0;
+        result.push(
+            "ASLR",
+            match (flags & IMAGE_DLLCHARACTERISTICS_DYNAMIC_BASE != 0, relocs) {
+                (true, true) => (
+                    CheckStatus::Pass,
+                    "DYNAMIC_BASE is set and relocations are present",
+                ),
+                (true, false) => (
+                    CheckStatus::Fail,
+                    "DYNAMIC_BASE is set but relocations are stripped",
+                ),
+                (false, _) => (CheckStatus::Fail, "DYNAMIC_BASE is not set"),
+            },
+        );
+        result.push(
+            "High entropy VA",
+            if !pe.0.is_64 {
+                (CheckStatus::NotApplicable, "32-bit image")
+            } else if flags & IMAGE_DLLCHARACTERISTICS_HIGH_ENTROPY_VA == 0 {
+                (CheckStatus::Fail, "HIGH_ENTROPY_VA is not set")
+            } else if flags & IMAGE_DLLCHARACTERISTICS_DYNAMIC_BASE == 0 {
+                (
+                    CheckStatus::Fail,
+                    "HIGH_ENTROPY_VA is set without DYNAMIC_BASE",
+                )
+            } else {
+                (CheckStatus::Pass, "HIGH_ENTROPY_VA is set")
+            },
+        );
+        result.push(
+            "DEP",
+            if flags & IMAGE_DLLCHARACTERISTICS_NX_COMPAT != 0 {
+                (CheckStatus::Pass, "NX_COMPAT is set")
+            } else {
+                (CheckStatus::Fail, "NX_COMPAT is not set")
+            },
+        );
+        let cf_instrumented = load_config.as_ref().is_some_and(|lc| {
+            lc.guard_cf_function_table.unwrap_or_default() != 0
+                || lc.guard_flags.unwrap_or_default() & IMAGE_GUARD_CF_INSTRUMENTED != 0
+        });
+        result.push(
+            "Control Flow Guard",
+            if managed {
+                (CheckStatus::NotApplicable, "managed image")
+            } else if flags & IMAGE_DLLCHARACTERISTICS_GUARD_CF == 0 {
+                (CheckStatus::Fail, "GUARD_CF is not set")
+            } else if !cf_instrumented {
+                (
+                    CheckStatus::Fail,
+                    "GUARD_CF is set but the load config has no CFG table",
+                )
+            } else {
+                (
+                    CheckStatus::Pass,
+                    "GUARD_CF is set and the load config has a CFG table",
+                )
+            },
+        );
+        let se_handlers = load_config
+            .as_ref()
+            .and_then(|lc| lc.se_handler_count)
+            .unwrap_or_default();
+        result.push(
+            "SafeSEH",
+            if !x86 {
+                (CheckStatus::NotApplicable, "only applies to x86 images")
+            } else if managed {
+                (CheckStatus::NotApplicable, "managed image")
+            } else if flags & IMAGE_DLLCHARACTERISTICS_NO_SEH != 0 {
+                (
+                    CheckStatus::Pass,
+                    "NO_SEH is set, the image has no exception handlers",
+                )
+            } else if se_handlers > 0 {
+                (
+                    CheckStatus::Pass,
+                    "the load config has a safe exception handler table",
+                )
+            } else {
+                (CheckStatus::Fail, "no safe exception handler table")
+            },
+        );
+        result.push(
+            "/GS",
+            if managed {
+                (CheckStatus::NotApplicable, "managed image")
+            } else if load_config.is_none() {
+                (CheckStatus::Fail, "no load config directory")
+            } else if load_config
+                .as_ref()
+                .and_then(|lc| lc.security_cookie)
+                .unwrap_or_default()
+                == 0
+            {
+                (CheckStatus::Fail, "the load config has no security cookie")
+            } else {
+                (CheckStatus::Pass, "the load config has a security cookie")
+            },
+        );
+        result.push(
+            "CET shadow stack",
+            if cet_compatible(pe) {
+                (CheckStatus::Pass, "CET_COMPAT is set")
+            } else {
+                (CheckStatus::Fail, "CET_COMPAT is not set")
+            },
+        );
+        let signed = !pe.0.certificates.is_empty();
+        result.push(
+            "FORCE_INTEGRITY",
+            match (
+                flags & IMAGE_DLLCHARACTERISTICS_FORCE_INTEGRITY != 0,
+                signed,
+            ) {
+                (true, true) => (
+                    CheckStatus::Pass,
+                    "FORCE_INTEGRITY is set and the image is signed",
+                ),
+                (true, false) => (
+                    CheckStatus::Fail,
+                    "FORCE_INTEGRITY is set but the image is not signed, it will not load",
+                ),
+                (false, _) => (CheckStatus::Fail, "FORCE_INTEGRITY is not set"),
+            },
+        );
+        let wx: Vec<String> =
+            pe.0.sections
+                .iter()
+                .filter(|sec| {
+                    sec.characteristics & IMAGE_SCN_MEM_WRITE != 0
+                        && sec.characteristics & IMAGE_SCN_MEM_EXECUTE != 0
+                })
+                .map(|sec| sec.name().unwrap_or_default().to_string())
+                .collect();
+        let reason = format!("writable and executable sections: {}", wx.join(", "));
+        result.push(
+            "No writable and executable sections",
+            if wx.is_empty() {
+                (
+                    CheckStatus::Pass,
+                    "no section is both writable and executable",
+                )
+            } else {
+                (CheckStatus::Fail, &reason)
+            },
+        );
+        result.push(
+            "Signed",
+            if signed {
+                (CheckStatus::Pass, "the image has an Authenticode signature")
+            } else {
+                (CheckStatus::Fail, "the image is not signed")
+            },
+        );
+        Ok(result)
+    }
+
+    fn push(&mut self, name: &str, (status, reason): (CheckStatus, &str)) {
+        self.checks.push(MitigationCheck {
+            name: name.to_string(),
+            status,
+            reason: reason.to_string(),
+        });
+    }
+}
+
+/// Looks for `IMAGE_DLLCHARACTERISTICS_EX_CET_COMPAT` in the debug directory.
+fn cet_compatible(pe: (&PE, &[u8])) -> bool {
+    let Some(dd) =
+        pe.0.header
+            .optional_header
+            .and_then(|oh| oh.data_directories.get_debug_table().copied())
+    else {
+        return false;
+    };
+    let Some(offset) = rva_to_offset(pe.0, dd.virtual_address) else {
+        return false;
+    };
+    safe_read(pe.1, offset, dd.size as usize)
+        .chunks_exact(SIZEOF_DEBUG_DIRECTORY)
+        .filter(|entry| le_u32(entry, 12) == Some(IMAGE_DEBUG_TYPE_EX_DLLCHARACTERISTICS))
+        .any(|entry| {
+            let data = safe_read(pe.1, le_u32(entry, 24).unwrap_or_default() as usize, 4);
+            le_u32(data, 0).unwrap_or_default() & IMAGE_DLLCHARACTERISTICS_EX_CET_COMPAT != 0
+        })
+}
@@ -1,9 +1,14 @@
 use crate::{
     entry_point::EntryPoint,
+    go::GoInfo,
+    installer::Installer,
+    packers::Packers,
     pe::{
-        hash::Hashes, imports::Imports, resource::Resources, rich_headers::RichTable,
-        signatures::PeAuthenticodes, tls::TlsCallbacks,
+        hash::Hashes, imports::Imports, mitigations::Mitigations, resource::Resources,
+        rich_headers::RichTable, signatures::PeAuthenticodes, tls::TlsCallbacks,
     },
+    python::PythonInfo,
+    rust::RustInfo,
     sections::SectionTable,
     Result,
 };
@@ -12,6 +17,7 @@ use serde::Serialize;
 
 pub mod hash;
 pub mod imports;
+pub mod mitigations;
 pub mod resource;
 pub mod rich_headers;
 pub mod signatures;
@@ -28,11 +34,17 @@ pub struct PeFileInformation {
     pub imports: Imports,
     pub resources: Option<Resources>,
     pub tls: TlsCallbacks,
+    pub mitigations: Mitigations,
+    pub go: Option<GoInfo>,
+    pub rust: Option<RustInfo>,
+    pub packers: Packers,
+    pub installer: Option<Installer>,
+    pub python: Option<PythonInfo>,
 }
 
 impl PeFileInformation {
     pub fn parse(pe: (&PE, &[u8])) -> Result<PeFileInformation> {
-        Ok(PeFileInformation {
+        let mut info = PeFileInformation {
             entry_point: EntryPoint::try_from(pe)?,
             hashes: Hashes::parse(pe.1),
             signature: PeAuthenticodes::parse(pe)?,
@@ -41,6 +53,16 @@ impl PeFileInformation {
             imports: Imports::parse(pe)?,
             resources: Resources::parse(pe)?,
             tls: TlsCallbacks::parse(pe)?,
-        })
+            mitigations: Mitigations::parse(pe)?,
+            go: GoInfo::from_pe(pe),
+            rust: RustInfo::from_pe(pe),
+            packers: Packers::default(),
+            installer: None,
+            python: None,
+        };
+        info.packers = Packers::from_pe(pe, &info.section_table, &info.imports);
+        info.installer = Installer::from_pe(pe, info.packers.overlay.as_ref());
+        info.python = PythonInfo::from_pe(pe, info.packers.overlay.as_ref());
+        Ok(info)
     }
 }
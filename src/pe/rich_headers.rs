@@ -1,13 +1,30 @@
-use crate::{compare_default_impl, error::Error, pe::util::Comparable};
+use crate::{
+    compare_default_impl,
+    database::SignatureDatabase,
+    error::Error,
+    pe::{
+        hash::HashData,
+        util::{pe_checksum, Comparable},
+    },
+};
 use goblin::pe::PE;
 use phf::phf_map;
 use serde::{Deserialize, Serialize};
-use std::{collections::HashMap, fmt, iter, mem};
+use std::{
+    collections::{hash_map::Entry, HashMap, HashSet},
+    fmt, iter, mem,
+    sync::RwLock,
+};
 
 const COMPS: &str = include_str!("../../assets/comp_id.txt");
 
+/// Description of a comp.id that no database knows.
+const UNKNOWN_PRODUCT: &str = "UNKNOWN PRODUCT";
+
 lazy_static::lazy_static! {
-    static ref COMP_ID_MAP: HashMap<u32, &'static str> = get_compid_map();
+    /// Loaded comp.id databases, later ones take precedence.
+    static ref COMP_ID_DATABASES: RwLock<Vec<CompIdDatabase>> =
+        RwLock::new(vec![CompIdDatabase::builtin()]);
 }
 
 /// From https://github.com/RichHeaderResearch/RichPE/blob/master/spoof_check.py
@@ -269,6 +286,12 @@ pub struct RichRecord {
     pub product_id: u16,
     pub count: u32,
     pub guessed_visual_studio_version: String,
+    /// Version of the comp.id database the guessed version comes from.
+    #[serde(default)]
+    pub comp_id_database: Option<String>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    #[cfg_attr(not(feature = "serde"), serde(skip))]
+    pub object_kind: ObjectKind,
 }
 
 impl RichRecord {
@@ -284,24 +307,65 @@ impl RichRecord {
             product_id: product,
             count,
             guessed_visual_studio_version: "".to_string(),
+            comp_id_database: None,
+            object_kind: ObjectKind::from(product),
         };
         rr.product_name = rr.get_product_name().to_string();
-        rr.guessed_visual_studio_version = rr.lookup_vs_version().to_string();
+        if let Some((description, version)) = rr.lookup_comp_id() {
+            rr.guessed_visual_studio_version = description;
+            rr.comp_id_database = Some(version);
+        } else {
+            rr.guessed_visual_studio_version = UNKNOWN_PRODUCT.to_string();
+        }
+        if rr.object_kind == ObjectKind::Unknown {
+            rr.object_kind = ObjectKind::from(rr.product_name.as_str());
+        }
         rr
     }
     /// Encodes the record with the given key.
     pub fn encode(&self, key: u32) -> [u32; 2] {
-        let value = (self.product_id as u32) << 16 | (self.build as u32);
-        [value ^ key, self.count ^ key]
+        [self.comp_id() ^ key, self.count ^ key]
+    }
+
+    /// Returns the comp.id, the product id in the high word and the build in the low word.
+    pub fn comp_id(&self) -> u32 {
+        (self.product_id as u32) << 16 | (self.build as u32)
+    }
+
+    /// Looks up the comp.id in the loaded databases, including the ones loaded at runtime.
+    pub fn lookup_vs_version(&self) -> String {
+        match self.lookup_comp_id() {
+            Some((description, _)) => description,
+            _ => UNKNOWN_PRODUCT.to_string(),
+        }
+    }
+
+    /// Looks up the comp.id of the record in the loaded databases.
+    ///
+    /// Returns the description and the version of the database that matched.
+    pub fn lookup_comp_id(&self) -> Option<(String, String)> {
+        let comp_id = self.comp_id();
+        CompIdDatabase::loaded().iter().rev().find_map(|database| {
+            database
+                .entries
+                .get(&comp_id)
+                .map(|description| (description.clone(), database.version.clone()))
+        })
     }
 
-    pub fn lookup_vs_version(&self) -> &'static str {
-        match COMP_ID_MAP.get(&(((self.product_id as u32) << 16) | self.build as u32)) {
-            Some(dd) => dd,
-            _ => "UNKNOWN PRODUCT",
+    /// Returns the guessed Visual Studio version, if the comp.id is known.
+    pub fn known_vs_version(&self) -> Option<&str> {
+        match self.guessed_visual_studio_version.as_str() {
+            "" | UNKNOWN_PRODUCT => None,
+            vs_version => Some(vs_version),
         }
     }
 
+    /// Returns the product name without the object flavour, e.g. `Utc1900` for `Utc1900_CPP`.
+    pub fn product_family(&self) -> &str {
+        self.product_name.split('_').next().unwrap_or_default()
+    }
+
     pub fn get_product_name(&self) -> &'static str {
         KNOWN_PRODUCT_IDS
             .contains_key(&self.product_id)
@@ -371,6 +435,14 @@ impl<'a> RichStructure<'a> {
     pub fn image(&self) -> &'a [u32] {
         self.image
     }
+    /// Returns the file offset of the `DanS` marker.
+    pub fn offset(&self) -> usize {
+        mem::size_of_val(self.dos_stub)
+    }
+    /// Returns the size in bytes of the Rich image, from `DanS` up to and including the xor key.
+    pub fn size(&self) -> usize {
+        mem::size_of_val(self.image)
+    }
     /// Calculate the checksum.
     ///
     /// The checksum should be equal to the xor key.
@@ -468,14 +540,35 @@ impl<'a> fmt::Debug for RichStructure<'a> {
 pub struct RichTable {
     pub rich_entries: Vec<RichRecord>,
     pub key: u32,
+    /// Checksum computed over the DOS stub and the decoded records.
+    #[serde(default)]
+    pub checksum: u32,
+    /// Whether the computed checksum matches the xor key.
+    #[serde(default)]
+    pub checksum_valid: bool,
+    /// File offset of the `DanS` marker.
+    #[serde(default)]
+    pub offset: usize,
+    /// Size in bytes of the header, from `DanS` up to and including the xor key.
+    #[serde(default)]
+    pub size: usize,
+    /// Likelihood that the header was forged or transplanted from another binary.
+    #[serde(default)]
+    pub spoof_check: SpoofCheck,
+    /// Summary of the tools that built the image.
+    #[serde(default)]
+    pub toolchain: RichToolchain,
+    /// MD5 of the decoded header, see [`RichTable::rich_hash`].
+    #[serde(default)]
+    pub rich_hash: String,
+    /// MD5 of the product ids and builds, see [`RichTable::rich_pv_hash`].
+    #[serde(default)]
+    pub rich_pv_hash: String,
 }
 
 impl RichTable {
     pub fn parse(pe: (&PE, &[u8])) -> RichTable {
-        let mut rich_table: RichTable = RichTable {
-            rich_entries: vec![],
-            key: 0,
-        };
+        let mut rich_table: RichTable = RichTable::default();
         if pe.1.len() < 0x400 {
             return rich_table;
         }
@@ -487,13 +580,525 @@ impl RichTable {
         };
 
         rich_table.key = rich_header.xor_key();
+        rich_table.checksum = rich_header.checksum();
+        rich_table.checksum_valid = rich_table.checksum == rich_table.key;
+        rich_table.offset = rich_header.offset();
+        rich_table.size = rich_header.size();
 
         for record in rich_header.records() {
             rich_table.rich_entries.push(record);
         }
 
+        rich_table.spoof_check = SpoofCheck::assess(&rich_table, pe.0);
+        rich_table.toolchain = RichToolchain::from(&rich_table);
+        rich_table.rich_hash = rich_table.rich_hash();
+        rich_table.rich_pv_hash = rich_table.rich_pv_hash();
+
         rich_table
     }
+
+    /// MD5 of the decoded header, from `DanS` up to the `Rich` marker.
+    ///
+    /// Matches the hash of `pe.rich_signature.clear_data` in YARA.
+    pub fn rich_hash(&self) -> String {
+        if self.rich_entries.is_empty() {
+            return String::new();
+        }
+        let mut clear_data = Vec::with_capacity(16 + self.rich_entries.len() * 8);
+        clear_data.extend_from_slice(&DANS_MARKER.to_le_bytes());
+        clear_data.extend_from_slice(&[0; 12]);
+        for record in self.rich_entries.iter() {
+            clear_data.extend_from_slice(&record.comp_id().to_le_bytes());
+            clear_data.extend_from_slice(&record.count.to_le_bytes());
+        }
+        String::from_utf8_lossy(&clear_data.md5()).to_string()
+    }
+
+    /// MD5 of the comp.ids (product id and build) only, leaving out the object counts.
+    ///
+    /// Binaries built by the same toolchain from slightly different sources share it.
+    pub fn rich_pv_hash(&self) -> String {
+        if self.rich_entries.is_empty() {
+            return String::new();
+        }
+        let comp_ids: Vec<u8> = self
+            .rich_entries
+            .iter()
+            .flat_map(|record| record.comp_id().to_le_bytes())
+            .collect();
+        String::from_utf8_lossy(&comp_ids.md5()).to_string()
+    }
+
+    /// Similarity between two tables, between 0 and 1.
+    ///
+    /// Tables without records are not similar to anything.
+    pub fn similarity(&self, other: &RichTable, metric: RichSimilarity) -> f64 {
+        let counts = |table: &RichTable| {
+            table
+                .rich_entries
+                .iter()
+                .fold(HashMap::new(), |mut acc, record| {
+                    *acc.entry(record.comp_id()).or_insert(0u64) += record.count as u64;
+                    acc
+                })
+        };
+        let (left, right) = (counts(self), counts(other));
+        if left.is_empty() || right.is_empty() {
+            return 0.0;
+        }
+        let (mut intersection, mut union) = (0u64, 0u64);
+        for comp_id in left
+            .keys()
+            .chain(right.keys().filter(|k| !left.contains_key(k)))
+        {
+            let l = left.get(comp_id).copied();
+            let r = right.get(comp_id).copied();
+            match metric {
+                RichSimilarity::Jaccard => {
+                    intersection += (l.is_some() && r.is_some()) as u64;
+                    union += 1;
+                }
+                RichSimilarity::Weighted => {
+                    let (l, r) = (l.unwrap_or(0), r.unwrap_or(0));
+                    intersection += l.min(r);
+                    union += l.max(r);
+                }
+            }
+        }
+        match union {
+            0 => 0.0,
+            _ => intersection as f64 / union as f64,
+        }
+    }
+
+    /// Groups tables whose similarity is at least `threshold` (single linkage).
+    ///
+    /// Returns the indices of the tables in each cluster, every index appears exactly once.
+    /// With `Jaccard`, tables sharing a Rich PV hash have the same comp.ids and so the same
+    /// similarity to every other table, they are merged up front and only one of them is
+    /// compared against the rest of the corpus. With `Weighted` the object counts differ,
+    /// every table is compared.
+    pub fn cluster(
+        tables: &[RichTable],
+        threshold: f64,
+        metric: RichSimilarity,
+    ) -> Vec<Vec<usize>> {
+        fn find(parents: &mut [usize], mut i: usize) -> usize {
+            while parents[i] != i {
+                parents[i] = parents[parents[i]];
+                i = parents[i];
+            }
+            i
+        }
+        fn union(parents: &mut [usize], a: usize, b: usize) {
+            let (a, b) = (find(parents, a), find(parents, b));
+            if a != b {
+                parents[b] = a;
+            }
+        }
+
+        let mut parents: Vec<usize> = (0..tables.len()).collect();
+        let mut representatives: Vec<usize> = vec![];
+        let mut by_pv_hash: HashMap<String, usize> = HashMap::new();
+        for (i, table) in tables.iter().enumerate() {
+            if table.rich_entries.is_empty() {
+                continue;
+            }
+            if metric == RichSimilarity::Jaccard {
+                match by_pv_hash.entry(table.rich_pv_hash()) {
+                    Entry::Occupied(first) => {
+                        union(&mut parents, *first.get(), i);
+                        continue;
+                    }
+                    Entry::Vacant(entry) => {
+                        entry.insert(i);
+                    }
+                }
+            }
+            representatives.push(i);
+        }
+        for (n, &i) in representatives.iter().enumerate() {
+            for &j in representatives[n + 1..].iter() {
+                if find(&mut parents, i) != find(&mut parents, j)
+                    && tables[i].similarity(&tables[j], metric) >= threshold
+                {
+                    union(&mut parents, i, j);
+                }
+            }
+        }
+
+        let mut clusters: Vec<Vec<usize>> = vec![];
+        let mut cluster_of_root: HashMap<usize, usize> = HashMap::new();
+        for i in 0..tables.len() {
+            let root = find(&mut parents, i);
+            let cluster = *cluster_of_root.entry(root).or_insert_with(|| {
+                clusters.push(vec![]);
+                clusters.len() - 1
+            });
+            clusters[cluster].push(i);
+        }
+        clusters
+    }
+
+    /// Returns the record of the linker that produced the image, the newest one wins.
+    pub fn linker(&self) -> Option<&RichRecord> {
+        self.rich_entries
+            .iter()
+            .filter(|record| record.object_kind == ObjectKind::Link)
+            .max_by_key(|record| (record.product_id, record.build))
+    }
+}
+
+/// Similarity measure between the records of two Rich headers.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum RichSimilarity {
+    /// Jaccard index of the comp.ids, object counts are ignored.
+    Jaccard,
+    /// Weighted Jaccard index, the object counts of each comp.id are the weights.
+    Weighted,
+}
+
+/// Toolchain summary derived from the records of a Rich header.
+#[derive(PartialEq, Eq, Debug, Default, Hash, Clone, Deserialize, Serialize)]
+pub struct RichToolchain {
+    /// Version of the linker, e.g. `14.00.29914`.
+    pub linker_version: Option<String>,
+    /// Distinct compilers the C and C++ objects were built with.
+    pub compiler_versions: Vec<String>,
+    pub c_objects: u32,
+    pub cpp_objects: u32,
+    pub assembly_objects: u32,
+    /// Visual Studio release of the linker.
+    pub visual_studio: Option<String>,
+    /// One line description, e.g. `built with VS2019 v16.9.4 build 29914, 142 C++ objs, 3 MASM objs`.
+    pub summary: String,
+}
+
+impl From<&RichTable> for RichToolchain {
+    fn from(rich_table: &RichTable) -> Self {
+        let mut toolchain = RichToolchain::default();
+        for record in rich_table.rich_entries.iter() {
+            match record.object_kind {
+                ObjectKind::C => toolchain.c_objects += record.count,
+                ObjectKind::CPP => toolchain.cpp_objects += record.count,
+                ObjectKind::Assembly => toolchain.assembly_objects += record.count,
+                _ => continue,
+            }
+            if record.object_kind == ObjectKind::Assembly {
+                continue;
+            }
+            let family = match record.product_family() {
+                family if family.starts_with("Utc") => family.to_string(),
+                _ => format!("Product{}", record.product_id),
+            };
+            let compiler = match record.known_vs_version() {
+                Some(vs_version) => format!("{} {} ({})", family, record.build, vs_version),
+                None => format!("{} {}", family, record.build),
+            };
+            if !toolchain.compiler_versions.contains(&compiler) {
+                toolchain.compiler_versions.push(compiler);
+            }
+        }
+        if let Some(linker) = rich_table.linker() {
+            toolchain.linker_version = linker_version(linker)
+                .map(|(major, minor)| format!("{}.{:02}.{}", major, minor, linker.build));
+            toolchain.visual_studio = linker.known_vs_version().map(str::to_string).or_else(|| {
+                product_era(linker.product_id).map(|era| PRODUCT_ERAS[era].2.to_string())
+            });
+        }
+        toolchain.summary = toolchain.to_string();
+        toolchain
+    }
+}
+
+impl fmt::Display for RichToolchain {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match (&self.visual_studio, &self.linker_version) {
+            (Some(visual_studio), _) => write!(f, "built with {}", visual_studio)?,
+            (None, Some(linker_version)) => write!(f, "built with linker {}", linker_version)?,
+            (None, None) => write!(f, "built with an unknown toolchain")?,
+        }
+        for (count, kind) in [
+            (self.cpp_objects, "C++"),
+            (self.c_objects, "C"),
+            (self.assembly_objects, "MASM"),
+        ] {
+            if count > 0 {
+                write!(f, ", {} {} objs", count, kind)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Change to apply to the Rich header of a PE image with [`RichTable::rewrite`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RichEdit {
+    /// Replace the records, the xor key is recomputed for the new records.
+    Replace(Vec<RichRecord>),
+    /// Remove the Rich header, leaving the DOS stub zero padded up to the PE header.
+    Strip,
+}
+
+impl RichTable {
+    /// Applies `edit` to the Rich header of the PE image `pe` and returns the modified image.
+    ///
+    /// The new header is written where the old one started and zero padded up to `e_lfanew`,
+    /// fails with [`Error::Bounds`] if the records do not fit before the PE header.
+    /// The optional header checksum is updated when the original image had one.
+    pub fn rewrite(pe: &[u8], edit: &RichEdit) -> Result<Vec<u8>, Error> {
+        let e_lfanew = pe
+            .get(0x3c..0x40)
+            .map(|bytes| u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as usize)
+            .ok_or(Error::Bounds)?;
+        if e_lfanew > pe.len() {
+            return Err(Error::Bounds);
+        }
+        let dwords: Vec<u32> = pe[..e_lfanew]
+            .chunks_exact(4)
+            .map(|dword| u32::from_le_bytes([dword[0], dword[1], dword[2], dword[3]]))
+            .collect();
+        let rich_header = RichStructure::try_from(&dwords)?;
+        let start = rich_header.offset();
+
+        let mut image = pe.to_vec();
+        image[start..e_lfanew].fill(0);
+        if let RichEdit::Replace(records) = edit {
+            // The linker pads the header depending on the key, drop the padding if needed.
+            let len = match rich_header.encode(records, &mut []) {
+                Ok(len) | Err(len) => len,
+            }
+            .min((e_lfanew - start) / 4);
+            if len < records.len() * 2 + 6 {
+                return Err(Error::Bounds);
+            }
+            let mut encoded = vec![0u32; len];
+            rich_header
+                .encode(records, &mut encoded)
+                .map_err(|_| Error::Invalid)?;
+            for (i, dword) in encoded.iter().enumerate() {
+                image[start + i * 4..start + i * 4 + 4].copy_from_slice(&dword.to_le_bytes());
+            }
+        }
+
+        // The checksum lives at the same offset in PE32 and PE32+ optional headers.
+        let checksum_offset = e_lfanew + 4 + 20 + 64;
+        if let Some(checksum) = image.get(checksum_offset..checksum_offset + 4) {
+            if checksum.iter().any(|&b| b != 0) {
+                let checksum = pe_checksum(&image, checksum_offset);
+                image[checksum_offset..checksum_offset + 4]
+                    .copy_from_slice(&checksum.to_le_bytes());
+            }
+        }
+        Ok(image)
+    }
+}
+
+/// How likely it is that a Rich header does not describe the binary it is found in.
+#[derive(
+    Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Debug, Default, Hash, Deserialize, Serialize,
+)]
+pub enum SpoofLikelihood {
+    #[default]
+    None,
+    Low,
+    Medium,
+    High,
+}
+
+/// Result of the consistency checks run against a Rich header.
+///
+/// Loosely follows the checks of `spoof_check.py` from RichPE: the header is compared
+/// against itself (checksum, duplicates, product/build combinations) and against the rest
+/// of the PE (linker version in the optional header, import table).
+#[derive(PartialEq, Eq, Debug, Default, Hash, Clone, Deserialize, Serialize)]
+pub struct SpoofCheck {
+    pub likelihood: SpoofLikelihood,
+    /// Human readable description of every check that failed.
+    pub findings: Vec<String>,
+}
+
+/// Product id ranges of the Visual Studio releases whose build numbers are well documented,
+/// in release order, together with the range of builds each release shipped with.
+const PRODUCT_ERAS: &[(u16, u16, &str, u16, u16)] = &[
+    (0x006d, 0x0082, "VS2005", 40000, 50999),
+    (0x0083, 0x0097, "VS2008", 20000, 30999),
+    (0x0098, 0x00bc, "VS2010", 20000, 40999),
+    (0x00c7, 0x00d8, "VS2012", 40000, 61999),
+    (0x00d9, 0x00fc, "VS2013", 20000, 41999),
+    (0x00fd, 0x010e, "VS2015+", 21000, u16::MAX),
+];
+
+/// Product id 13, objects produced by the Visual Basic 6 compiler.
+const VISUAL_BASIC_60: u16 = 13;
+/// Product id 1, the imported functions of objects linked without an import library.
+const IMPORT_0: u16 = 1;
+
+/// Returns the index in `PRODUCT_ERAS` of the release the product id belongs to.
+fn product_era(product_id: u16) -> Option<usize> {
+    PRODUCT_ERAS
+        .iter()
+        .position(|(first, last, ..)| (*first..=*last).contains(&product_id))
+}
+
+/// Whether the product id is in the product map, in the id range of a release or in a
+/// comp.id of a loaded database.
+fn known_product(product_id: u16) -> bool {
+    KNOWN_PRODUCT_IDS.contains_key(&product_id)
+        || product_era(product_id).is_some()
+        || CompIdDatabase::loaded().iter().any(|database| {
+            database
+                .entries
+                .keys()
+                .any(|comp_id| (comp_id >> 16) as u16 == product_id)
+        })
+}
+
+/// Returns the major and minor version encoded in a `LinkerXXYY` product name.
+fn linker_version(record: &RichRecord) -> Option<(u32, u32)> {
+    record
+        .product_family()
+        .strip_prefix("Linker")
+        .and_then(|version| version.trim().parse::<u32>().ok())
+        .map(|version| (version / 100, version % 100))
+}
+
+impl SpoofCheck {
+    pub fn assess(rich_table: &RichTable, pe: &PE) -> SpoofCheck {
+        let records = &rich_table.rich_entries;
+        let mut result = SpoofCheck::default();
+        if records.is_empty() {
+            return result;
+        }
+        let mut score = 0;
+
+        if !rich_table.checksum_valid {
+            score += 3;
+            result.findings.push(format!(
+                "checksum {:#010x} does not match the xor key {:#010x}",
+                rich_table.checksum, rich_table.key
+            ));
+        }
+
+        let linker = rich_table.linker();
+        if let (Some(linker), Some(optional_header)) = (linker, pe.header.optional_header) {
+            let header_major = optional_header.standard_fields.major_linker_version;
+            if let Some((rich_major, _)) = linker_version(linker) {
+                if rich_major != header_major as u32 {
+                    score += 3;
+                    result.findings.push(format!(
+                        "linker version {} in the Rich header does not match version {}.{} in the optional header",
+                        rich_major, header_major, optional_header.standard_fields.minor_linker_version
+                    ));
+                }
+            }
+        }
+
+        // Import libraries and resources are routinely stamped with SDK builds,
+        // only the tools that ship with a release are held to its build numbers.
+        let is_tool = |record: &&RichRecord| {
+            matches!(
+                record.object_kind,
+                ObjectKind::C | ObjectKind::CPP | ObjectKind::Assembly | ObjectKind::Link
+            )
+        };
+        for record in records.iter().filter(is_tool) {
+            if let Some(era) = product_era(record.product_id) {
+                let (_, _, name, min_build, max_build) = PRODUCT_ERAS[era];
+                if !(min_build..=max_build).contains(&record.build) {
+                    score += 2;
+                    result.findings.push(format!(
+                        "{} build {} was never shipped with {}",
+                        record.product_name, record.build, name
+                    ));
+                }
+            }
+        }
+        if let Some(unknown) = records
+            .iter()
+            .find(|record| !known_product(record.product_id))
+        {
+            score += 1;
+            result
+                .findings
+                .push(format!("unknown product id {}", unknown.product_id));
+        }
+
+        // Objects from a compiler newer than the linker cannot be linked.
+        if let Some(linker_era) = linker.and_then(|linker| product_era(linker.product_id)) {
+            let newer = records
+                .iter()
+                .filter(|record| {
+                    matches!(
+                        record.object_kind,
+                        ObjectKind::C | ObjectKind::CPP | ObjectKind::Assembly
+                    )
+                })
+                .find(|record| product_era(record.product_id).is_some_and(|era| era > linker_era));
+            if let Some(newer) = newer {
+                score += 2;
+                result.findings.push(format!(
+                    "{} objects are newer than the {} linker",
+                    newer.product_name, PRODUCT_ERAS[linker_era].2
+                ));
+            }
+        }
+
+        let mut seen = HashSet::new();
+        if records
+            .iter()
+            .any(|record| !seen.insert((record.product_id, record.build)))
+        {
+            score += 1;
+            result
+                .findings
+                .push("the same product and build appear in more than one record".to_string());
+        }
+
+        let has_vb6_runtime = pe
+            .imports
+            .iter()
+            .any(|import| import.dll.eq_ignore_ascii_case("msvbvm60.dll"));
+        if records
+            .iter()
+            .any(|record| record.product_id == VISUAL_BASIC_60 && record.count > 0)
+            && !has_vb6_runtime
+        {
+            score += 3;
+            result
+                .findings
+                .push("Visual Basic 6 objects without an import of MSVBVM60.DLL".to_string());
+        }
+
+        // Import libraries are objects of their own, only `Import0` counts functions.
+        let rich_imports: u64 = records
+            .iter()
+            .filter(|record| record.product_id == IMPORT_0)
+            .map(|record| record.count as u64)
+            .sum();
+        let pe_imports = pe.imports.len() as u64;
+        if pe_imports > rich_imports {
+            score += 2;
+            result.findings.push(format!(
+                "{} imported functions but only {} in the Rich header",
+                pe_imports, rich_imports
+            ));
+        } else if pe_imports == 0 && rich_imports > 0 {
+            score += 1;
+            result.findings.push(format!(
+                "{} imported functions in the Rich header but no import table",
+                rich_imports
+            ));
+        }
+
+        result.likelihood = match score {
+            0 => SpoofLikelihood::None,
+            1 => SpoofLikelihood::Low,
+            2..=3 => SpoofLikelihood::Medium,
+            _ => SpoofLikelihood::High,
+        };
+        result
+    }
 }
 
 compare_default_impl!(RichTable, RichRecord);
@@ -511,9 +1116,10 @@ impl IntoIterator for RichTable {
 ///
 /// Rich records can identify the product used and with it the _'language'_ of the objects.
 /// This allows a mapping of products and the kind of _'language'_ it was generated from.
-#[derive(Copy, Clone, Debug, Eq, PartialEq)]
-#[cfg_attr(feature = "serde", derive(::serde::Serialize))]
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]
 pub enum ObjectKind {
+    #[default]
     Unknown,
     Link,
     /// Exported symbol.
@@ -537,12 +1143,11 @@ impl From<u16> for ObjectKind {
             0x0100 | 0x00dc | 0x00ca | 0x009b | 0x0092 | 0x007a | 0x005c | 0x003f => {
                 ObjectKind::Export
             }
-            0x0101 | 0x00dd | 0x00cb | 0x009c | 0x0093 | 0x007b | 0x005d | 0x0019 | 0x0002 => {
+            0x0101 | 0x00dd | 0x00cb | 0x009c | 0x0093 | 0x007b | 0x005d | 0x0019 => {
                 ObjectKind::Import
             }
-            0x0102 | 0x00de | 0x00cc | 0x009d | 0x0091 | 0x0078 | 0x005a | 0x003d | 0x0004 => {
-                ObjectKind::Link
-            }
+            0x0102 | 0x00de | 0x00cc | 0x009d | 0x0091 | 0x0078 | 0x005a | 0x003d | 0x0004
+            | 0x0002 => ObjectKind::Link,
             0x0103 | 0x00df | 0x00cd | 0x009e | 0x0095 | 0x007d | 0x000f | 0x0040 => {
                 ObjectKind::Assembly
             }
@@ -556,6 +1161,34 @@ impl From<u16> for ObjectKind {
         }
     }
 }
+impl From<&str> for ObjectKind {
+    /// Maps a product name from `KNOWN_PRODUCT_IDS`, covers the LTCG, POGO and CVTCIL
+    /// flavours of the compilers that have no entry in the product id mapping.
+    fn from(product_name: &str) -> ObjectKind {
+        let family = product_name.split('_').next().unwrap_or_default();
+        if family.starts_with("Utc") {
+            if product_name.ends_with("_CPP") || product_name.contains("_CPP_") {
+                ObjectKind::CPP
+            } else if product_name.trim_end().ends_with("_C") || product_name.contains("_C_") {
+                ObjectKind::C
+            } else {
+                ObjectKind::Unknown
+            }
+        } else if family.starts_with("Masm") {
+            ObjectKind::Assembly
+        } else if family.starts_with("Linker") {
+            ObjectKind::Link
+        } else if family.starts_with("Implib") || family.starts_with("Import") {
+            ObjectKind::Import
+        } else if family.starts_with("Export") {
+            ObjectKind::Export
+        } else if family.starts_with("Cvtres") || family.starts_with("Resource") {
+            ObjectKind::Resource
+        } else {
+            ObjectKind::Unknown
+        }
+    }
+}
 
 #[derive(Clone)]
 pub struct RichIter<'a> {
@@ -611,10 +1244,35 @@ impl<'a> fmt::Debug for RichIter<'a> {
     }
 }
 
-/// Create the comp id map
-fn get_compid_map() -> HashMap<u32, &'static str> {
-    COMPS
-        .trim()
+/// A comp.id database, in the `<comp.id> [tag] description` format of `assets/comp_id.txt`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CompIdDatabase {
+    pub version: String,
+    pub entries: HashMap<u32, String>,
+}
+
+impl SignatureDatabase for CompIdDatabase {
+    const BUILTIN: &'static str = COMPS;
+    const COMMENT: char = '#';
+
+    fn parse(version: &str, data: &str) -> CompIdDatabase {
+        CompIdDatabase {
+            version: version.to_string(),
+            // Later duplicates overwrite earlier ones
+            entries: parse_comp_ids(data)
+                .map(|(comp_id, desc)| (comp_id, desc.to_string()))
+                .collect(),
+        }
+    }
+
+    fn databases() -> &'static RwLock<Vec<CompIdDatabase>> {
+        &COMP_ID_DATABASES
+    }
+}
+
+/// Parses the `<comp.id> [tag] description` lines of a comp.id database.
+fn parse_comp_ids(data: &str) -> impl Iterator<Item = (u32, &str)> {
+    data.trim()
         .lines()
         .filter(|line| !line.is_empty())
         .filter(|line| !line.starts_with('#') && !line.starts_with("00000000"))
@@ -628,9 +1286,221 @@ fn get_compid_map() -> HashMap<u32, &'static str> {
                 .map_or(line.trim(), |(_, second)| second.trim());
             u32::from_str_radix(comp_id, 16).map(|v| (v, desc)).ok()
         })
-        // Collect into a Vec first to remove duplicates
-        .collect::<Vec<(u32, &str)>>()
-        .into_iter()
-        // Then collect into a HashMap which will skip duplicates
-        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use goblin::pe::import::Import;
+
+    const E_LFANEW: usize = 0x100;
+    const RICH_OFFSET: usize = 0x80;
+    /// Offset of the checksum in the optional header.
+    const CHECKSUM_OFFSET: usize = E_LFANEW + 4 + 20 + 64;
+
+    fn record(product_id: u16, build: u16, count: u32) -> RichRecord {
+        RichRecord::decode(0, &[(product_id as u32) << 16 | build as u32, count])
+    }
+
+    /// A PE32 image without sections, with a Rich header for `records` after the DOS header.
+    fn image(records: &[RichRecord], linker_major: u8, checksum: u32) -> Vec<u8> {
+        let mut image = vec![0u8; 0x400];
+        image[..2].copy_from_slice(b"MZ");
+        image[0x3c..0x40].copy_from_slice(&(E_LFANEW as u32).to_le_bytes());
+        if !records.is_empty() {
+            let dos_stub: Vec<u32> = image[..RICH_OFFSET]
+                .chunks_exact(4)
+                .map(|dword| u32::from_le_bytes(dword.try_into().unwrap()))
+                .collect();
+            let key = RichStructure::_checksum(&dos_stub, records.iter().cloned());
+            let mut dwords = vec![DANS_MARKER ^ key, key, key, key];
+            dwords.extend(records.iter().flat_map(|record| record.encode(key)));
+            dwords.extend([RICH_MARKER, key]);
+            for (i, dword) in dwords.iter().enumerate() {
+                let offset = RICH_OFFSET + i * 4;
+                image[offset..offset + 4].copy_from_slice(&dword.to_le_bytes());
+            }
+        }
+        let header = &mut image[E_LFANEW..];
+        header[..4].copy_from_slice(b"PE\0\0");
+        // i386, no sections, a PE32 optional header, executable
+        header[4..6].copy_from_slice(&0x014cu16.to_le_bytes());
+        header[20..22].copy_from_slice(&0xe0u16.to_le_bytes());
+        header[22..24].copy_from_slice(&0x0102u16.to_le_bytes());
+        let optional = &mut header[24..];
+        optional[..2].copy_from_slice(&0x010bu16.to_le_bytes());
+        optional[2] = linker_major;
+        optional[28..32].copy_from_slice(&0x0040_0000u32.to_le_bytes());
+        optional[32..36].copy_from_slice(&0x1000u32.to_le_bytes());
+        optional[36..40].copy_from_slice(&0x200u32.to_le_bytes());
+        optional[56..60].copy_from_slice(&0x1000u32.to_le_bytes());
+        optional[60..64].copy_from_slice(&0x400u32.to_le_bytes());
+        optional[64..68].copy_from_slice(&checksum.to_le_bytes());
+        optional[68..70].copy_from_slice(&2u16.to_le_bytes());
+        optional[92..96].copy_from_slice(&16u32.to_le_bytes());
+        image
+    }
+
+    fn table(records: Vec<RichRecord>) -> RichTable {
+        RichTable {
+            rich_entries: records,
+            checksum_valid: true,
+            ..RichTable::default()
+        }
+    }
+
+    fn findings(records: Vec<RichRecord>, linker_major: u8, imports: &[&str]) -> SpoofCheck {
+        let image = image(&[], linker_major, 0);
+        let mut pe = PE::parse(&image).unwrap();
+        pe.imports = imports
+            .iter()
+            .map(|&name| Import {
+                name: name.into(),
+                dll: "KERNEL32.dll",
+                ordinal: 0,
+                offset: 0,
+                rva: 0,
+                size: 4,
+            })
+            .collect();
+        SpoofCheck::assess(&table(records), &pe)
+    }
+
+    fn has_finding(check: &SpoofCheck, text: &str) -> bool {
+        check.findings.iter().any(|finding| finding.contains(text))
+    }
+
+    #[test]
+    fn spoof_check_consistent() {
+        // Linker600 and Utc12_C objects in an image linked by version 6
+        let check = findings(vec![record(4, 8168, 1), record(10, 8168, 3)], 6, &[]);
+        assert_eq!(check.findings, Vec::<String>::new());
+        assert_eq!(check.likelihood, SpoofLikelihood::None);
+    }
+
+    #[test]
+    fn spoof_check_era() {
+        // a VS2010 C compiler with a build from a later release
+        let check = findings(vec![record(4, 8168, 1), record(0x00aa, 50000, 1)], 6, &[]);
+        assert!(has_finding(&check, "was never shipped with VS2010"));
+    }
+
+    #[test]
+    fn spoof_check_linker_version() {
+        let check = findings(vec![record(4, 8168, 1)], 14, &[]);
+        assert!(has_finding(&check, "linker version 6 in the Rich header"));
+        assert!(check.likelihood >= SpoofLikelihood::Medium);
+    }
+
+    #[test]
+    fn spoof_check_duplicates() {
+        let check = findings(vec![record(4, 8168, 1), record(4, 8168, 1)], 6, &[]);
+        assert!(has_finding(&check, "more than one record"));
+    }
+
+    #[test]
+    fn spoof_check_visual_basic() {
+        let check = findings(vec![record(4, 8168, 1), record(13, 9782, 1)], 6, &[]);
+        assert!(has_finding(&check, "MSVBVM60.DLL"));
+    }
+
+    #[test]
+    fn spoof_check_imports() {
+        // import library objects do not make up for the missing imported functions
+        let records = vec![record(4, 8168, 1), record(1, 0, 1), record(25, 4035, 100)];
+        let check = findings(records, 6, &["CreateFileA", "ReadFile", "CloseHandle"]);
+        assert!(has_finding(&check, "3 imported functions but only 1"));
+        let check = findings(vec![record(4, 8168, 1), record(1, 0, 2)], 6, &[]);
+        assert!(has_finding(&check, "but no import table"));
+        let check = findings(vec![record(4, 8168, 1), record(1, 0, 3)], 6, &["ReadFile"]);
+        assert!(!has_finding(&check, "imported functions"));
+    }
+
+    #[test]
+    fn spoof_check_unknown_product() {
+        let check = findings(vec![record(4, 8168, 1), record(0x7fff, 1, 1)], 6, &[]);
+        assert!(has_finding(&check, "unknown product id 32767"));
+    }
+
+    fn parse(image: &[u8]) -> RichTable {
+        RichTable::parse((&PE::parse(image).unwrap(), image))
+    }
+
+    fn comp_ids(table: &RichTable) -> Vec<(u16, u16, u32)> {
+        table
+            .rich_entries
+            .iter()
+            .map(|record| (record.product_id, record.build, record.count))
+            .collect()
+    }
+
+    #[test]
+    fn rewrite_replace() {
+        let original = image(&[record(4, 8168, 1), record(10, 8168, 3)], 6, 0);
+        assert_eq!(comp_ids(&parse(&original)), [(4, 8168, 1), (10, 8168, 3)]);
+        let records = vec![record(0x0102, 30729, 1), record(0x0105, 30729, 12)];
+        let rewritten = RichTable::rewrite(&original, &RichEdit::Replace(records)).unwrap();
+        assert_eq!(rewritten.len(), original.len());
+        assert_eq!(rewritten[E_LFANEW..], original[E_LFANEW..]);
+        let table = parse(&rewritten);
+        assert_eq!(comp_ids(&table), [(0x0102, 30729, 1), (0x0105, 30729, 12)]);
+        assert_eq!(table.offset, RICH_OFFSET);
+        assert_eq!(table.key, table.checksum);
+        assert!(table.checksum_valid);
+    }
+
+    #[test]
+    fn rewrite_strip() {
+        let original = image(&[record(4, 8168, 1)], 6, 0);
+        let stripped = RichTable::rewrite(&original, &RichEdit::Strip).unwrap();
+        assert!(stripped[RICH_OFFSET..E_LFANEW].iter().all(|&b| b == 0));
+        assert_eq!(stripped[..RICH_OFFSET], original[..RICH_OFFSET]);
+        assert!(parse(&stripped).rich_entries.is_empty());
+        // there is nothing left to rewrite
+        assert!(RichTable::rewrite(&stripped, &RichEdit::Strip).is_err());
+    }
+
+    #[test]
+    fn rewrite_bounds() {
+        let original = image(&[record(4, 8168, 1)], 6, 0);
+        // 6 dwords of header and 2 per record do not fit in the 0x80 bytes before the PE header
+        let records = (0..16).map(|i| record(10, i, 1)).collect();
+        assert!(matches!(
+            RichTable::rewrite(&original, &RichEdit::Replace(records)),
+            Err(Error::Bounds)
+        ));
+        let mut truncated = original.clone();
+        truncated[0x3c..0x40].copy_from_slice(&0x1000u32.to_le_bytes());
+        assert!(matches!(
+            RichTable::rewrite(&truncated, &RichEdit::Strip),
+            Err(Error::Bounds)
+        ));
+    }
+
+    #[test]
+    fn rewrite_checksum() {
+        let original = image(&[record(4, 8168, 1)], 6, 1);
+        for edit in [
+            RichEdit::Replace(vec![record(10, 8168, 2)]),
+            RichEdit::Strip,
+        ] {
+            let rewritten = RichTable::rewrite(&original, &edit).unwrap();
+            let checksum = u32::from_le_bytes(
+                rewritten[CHECKSUM_OFFSET..CHECKSUM_OFFSET + 4]
+                    .try_into()
+                    .unwrap(),
+            );
+            assert_eq!(checksum, pe_checksum(&rewritten, CHECKSUM_OFFSET));
+            let header = PE::parse(&rewritten)
+                .unwrap()
+                .header
+                .optional_header
+                .unwrap();
+            assert_eq!(header.windows_fields.check_sum, checksum);
+        }
+        // images without a checksum keep it zero
+        let original = image(&[record(4, 8168, 1)], 6, 0);
+        let rewritten = RichTable::rewrite(&original, &RichEdit::Strip).unwrap();
+        assert_eq!(rewritten[CHECKSUM_OFFSET..CHECKSUM_OFFSET + 4], [0; 4]);
+    }
 }
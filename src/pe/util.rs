@@ -1,25 +1,113 @@
+use crate::util::{le_u16, le_u32};
+use goblin::pe::PE;
 use std::hash::Hash;
 
-pub fn get_pe_file_size(pe: &[u8]) -> usize {
-    pe.len()
-    // pe.get_buffer().as_ref().len()
+/// Converts an RVA to a file offset using the section table.
+///
+/// RVAs below the first section are in the headers and map to the same offset.
+pub fn rva_to_offset(pe: &PE, rva: u32) -> Option<usize> {
+    for sec in &pe.sections {
+        let size = sec.virtual_size.max(sec.size_of_raw_data);
+        if rva >= sec.virtual_address && rva < sec.virtual_address.saturating_add(size) {
+            return (rva - sec.virtual_address)
+                .checked_add(sec.pointer_to_raw_data)
+                .map(|offset| offset as usize);
+        }
+    }
+    let headers_size = pe
+        .header
+        .optional_header
+        .map(|oh| oh.windows_fields.size_of_headers)
+        .unwrap_or_default();
+    (rva < headers_size).then_some(rva as usize)
 }
 
-pub fn round_to_pe_sz_with_offset(pe: &[u8], offset: usize, value: usize) -> usize {
-    let pe_sz = get_pe_file_size(pe);
-    match offset + value > pe_sz {
-        true => pe_sz - offset,
-        false => value,
-    }
+/// Resource type or name, either a numeric id or a string.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ResourceId<'a> {
+    Id(u32),
+    Name(&'a str),
 }
-pub fn safe_read(pe: &[u8], offset: usize, size: usize) -> &[u8] {
-    let pe_sz = get_pe_file_size(pe);
-    if offset >= pe_sz {
-        return &pe[0..0];
+
+/// `RT_RCDATA` resource type
+pub const RT_RCDATA: u32 = 10;
+
+/// Returns the data of a resource, the first language is used when there are several.
+///
+/// Names are compared case-insensitively like `FindResource` does.
+pub fn find_resource<'a>(
+    pe: &PE,
+    data: &'a [u8],
+    kind: ResourceId,
+    name: ResourceId,
+) -> Option<&'a [u8]> {
+    let table = pe
+        .header
+        .optional_header
+        .and_then(|oh| oh.data_directories.get_resource_table().copied())?;
+    let rsrc = data.get(rva_to_offset(pe, table.virtual_address)?..)?;
+    let name_dir = resource_subdirectory(rsrc, 0, Some(kind))?;
+    let language_dir = resource_subdirectory(rsrc, name_dir, Some(name))?;
+    let entry = resource_entry(rsrc, language_dir, None)?;
+    if entry & 0x8000_0000 != 0 {
+        return None;
     }
+    let entry = entry as usize;
+    let rva = le_u32(rsrc, entry)?;
+    let size = le_u32(rsrc, entry + 4)?;
+    let offset = rva_to_offset(pe, rva)?;
+    data.get(offset..offset.checked_add(size as usize)?)
+}
+
+fn resource_subdirectory(rsrc: &[u8], directory: usize, id: Option<ResourceId>) -> Option<usize> {
+    let entry = resource_entry(rsrc, directory, id)?;
+    (entry & 0x8000_0000 != 0).then_some((entry & 0x7fff_ffff) as usize)
+}
 
-    let safe_size = round_to_pe_sz_with_offset(pe, offset, size);
-    &pe[offset..offset + safe_size]
+/// Offset field of the matching entry of a resource directory, the first entry for `None`.
+fn resource_entry(rsrc: &[u8], directory: usize, id: Option<ResourceId>) -> Option<u32> {
+    let u16_at = |offset: usize| le_u16(rsrc, offset);
+    let u32_at = |offset: usize| le_u32(rsrc, offset);
+    let count = u16_at(directory + 12)? as usize + u16_at(directory + 14)? as usize;
+    (0..count).find_map(|i| {
+        let entry = directory + 16 + i * 8;
+        let entry_name = u32_at(entry)?;
+        let matched = match id {
+            None => true,
+            Some(ResourceId::Id(id)) => entry_name == id,
+            Some(ResourceId::Name(name)) if entry_name & 0x8000_0000 != 0 => {
+                let offset = (entry_name & 0x7fff_ffff) as usize;
+                let len = u16_at(offset)? as usize;
+                let units = (0..len)
+                    .map(|i| u16_at(offset + 2 + i * 2))
+                    .collect::<Option<Vec<_>>>()?;
+                String::from_utf16_lossy(&units).eq_ignore_ascii_case(name)
+            }
+            Some(ResourceId::Name(_)) => false,
+        };
+        if matched {
+            u32_at(entry + 4)
+        } else {
+            None
+        }
+    })
+}
+
+/// Computes the optional header checksum of a PE image.
+///
+/// `checksum_offset` is the file offset of the `CheckSum` field, which is skipped.
+pub fn pe_checksum(pe: &[u8], checksum_offset: usize) -> u32 {
+    let mut sum: u64 = 0;
+    for (i, word) in pe.chunks(2).enumerate() {
+        let offset = i * 2;
+        if offset == checksum_offset || offset == checksum_offset + 2 {
+            continue;
+        }
+        sum += u16::from_le_bytes([word[0], word.get(1).copied().unwrap_or(0)]) as u64;
+        sum = (sum & 0xffff) + (sum >> 16);
+    }
+    sum = (sum & 0xffff) + (sum >> 16);
+    (sum as u32).wrapping_add(pe.len() as u32)
 }
 
 #[allow(unused)]
@@ -0,0 +1,790 @@
+use crate::{
+    error::Error,
+    installer::zip,
+    packers::Overlay,
+    pe::util::{find_resource, ResourceId, RT_RCDATA},
+    util::{be_u32, find, find_ignore_case, le_u32, rfind, safe_read},
+};
+use flate2::read::ZlibDecoder;
+use goblin::pe::PE;
+use serde::{Deserialize, Serialize};
+use std::io::Read;
+
+/// Start of the PyInstaller CArchive cookie
+const PYINSTALLER_MAGIC: &[u8] = b"MEI\x0c\x0b\x0a\x0b\x0e";
+/// Cookie of PyInstaller 2.0, without the Python library name
+const PYINSTALLER_COOKIE_SIZE_20: usize = 24;
+const PYINSTALLER_COOKIE_SIZE: usize = 88;
+/// Entry length, offset, size, uncompressed size, compression flag and type
+const PYINSTALLER_TOC_ENTRY_SIZE: usize = 18;
+const PYZ_MAGIC: &[u8] = b"PYZ\0";
+/// Bootstrap scripts and runtime hooks PyInstaller runs before the entry script
+const PYINSTALLER_BOOTSTRAP_PREFIXES: [&str; 2] = ["pyiboot", "pyi_rth_"];
+const PYINSTALLER_CRYPTO_KEY: &str = "pyimod00_crypto_key";
+const PY2EXE_SCRIPT_TYPE: &str = "PYTHONSCRIPT";
+const PY2EXE_MAGIC: u32 = 0x7856_3412;
+/// Tag, optimize, unbuffered and code size before the zip path
+const PY2EXE_HEADER_SIZE: usize = 16;
+/// Resource id of the Nuitka onefile payload
+const NUITKA_PAYLOAD_ID: u32 = 27;
+/// `KA` followed by `X` for a plain payload or `Y` for a zstd compressed one
+const NUITKA_PAYLOAD_MAGIC: &[u8] = b"KA";
+/// Strings of the Nuitka onefile bootstrap and of compiled functions
+const NUITKA_MARKERS: [&[u8]; 3] = [
+    b"NUITKA_ONEFILE_PARENT",
+    b"__nuitka_binary_dir",
+    b"compiled_function\0",
+];
+/// Upper bound for a decompressed member
+const MAX_MEMBER_SIZE: u64 = 256 * 1024 * 1024;
+/// Containers of a marshalled table of contents are not nested deeper than this
+const MAX_MARSHAL_DEPTH: usize = 64;
+
+/// Entry of the table of contents of a Python archive.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct PythonMember {
+    pub name: String,
+    /// e.g. `script`, `module`, `binary`, `PYZ module` or `library`
+    pub kind: String,
+    /// File offset of the stored data, absent when it cannot be read from the file directly
+    pub offset: Option<u64>,
+    /// Size of the stored data
+    pub size: u64,
+    pub uncompressed_size: Option<u64>,
+    /// zlib compressed
+    pub compressed: bool,
+}
+
+impl PythonMember {
+    /// Reads the member from the file it was listed from, decompressed.
+    ///
+    /// Scripts and PYZ modules are marshalled code objects without a `.pyc` header.
+    pub fn extract(&self, data: &[u8]) -> Result<Vec<u8>, Error> {
+        let offset =
+            usize::try_from(self.offset.ok_or(Error::Unmapped)?).map_err(|_| Error::Overflow)?;
+        let size = usize::try_from(self.size).map_err(|_| Error::Overflow)?;
+        let stored = data
+            .get(offset..offset.checked_add(size).ok_or(Error::Overflow)?)
+            .ok_or(Error::Bounds)?;
+        if !self.compressed {
+            return Ok(stored.to_vec());
+        }
+        let mut out = vec![];
+        ZlibDecoder::new(stored)
+            .take(
+                self.uncompressed_size
+                    .unwrap_or(MAX_MEMBER_SIZE)
+                    .min(MAX_MEMBER_SIZE),
+            )
+            .read_to_end(&mut out)
+            .map_err(|_| Error::UnsupportedCompression)?;
+        Ok(out)
+    }
+}
+
+/// Python program packaged into a native executable.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct PythonInfo {
+    /// `PyInstaller`, `py2exe` or `Nuitka`
+    pub packager: String,
+    /// e.g. `3.11`
+    pub python_version: Option<String>,
+    /// e.g. `python311.dll` or `libpython3.11.so.1.0`
+    pub python_library: Option<String>,
+    /// Scripts run at startup, without the packager's own bootstrap scripts
+    pub entry_scripts: Vec<String>,
+    /// File offset of the archive
+    pub offset: Option<u64>,
+    /// Other properties, e.g. an encrypted PYZ or the py2exe library path
+    pub details: Vec<String>,
+    /// Table of contents of the embedded archives
+    pub members: Vec<PythonMember>,
+}
+
+impl PythonInfo {
+    fn new(packager: &str, data: &[u8]) -> PythonInfo {
+        let python_library = python_library(data);
+        PythonInfo {
+            packager: packager.to_string(),
+            python_version: python_library.as_deref().and_then(library_version),
+            python_library,
+            ..Default::default()
+        }
+    }
+
+    /// Returns `None` for binaries not packaged by PyInstaller, py2exe or Nuitka.
+    pub fn from_pe(pe: (&PE, &[u8]), overlay: Option<&Overlay>) -> Option<PythonInfo> {
+        PythonInfo::pyinstaller(pe.1)
+            .or_else(|| PythonInfo::py2exe(pe, overlay))
+            .or_else(|| {
+                let payload = find_resource(
+                    pe.0,
+                    pe.1,
+                    ResourceId::Id(RT_RCDATA),
+                    ResourceId::Id(NUITKA_PAYLOAD_ID),
+                )
+                .map(|payload| (payload.as_ptr() as usize - pe.1.as_ptr() as usize, payload));
+                // Windows file names in the payload are UTF-16
+                PythonInfo::nuitka(pe.1, payload, 2)
+            })
+    }
+
+    /// Returns `None` for binaries not packaged by PyInstaller or Nuitka.
+    pub fn from_elf(data: &[u8], overlay: Option<&Overlay>) -> Option<PythonInfo> {
+        PythonInfo::pyinstaller(data).or_else(|| {
+            let payload = overlay.map(|overlay| {
+                let offset = overlay.offset as usize;
+                (offset, safe_read(data, offset, overlay.size as usize))
+            });
+            PythonInfo::nuitka(data, payload, 1)
+        })
+    }
+
+    /// The CArchive ends with a cookie pointing back at its table of contents. The
+    /// bootloader has the cookie magic as a constant too, so the last valid one is used.
+    fn pyinstaller(data: &[u8]) -> Option<PythonInfo> {
+        let mut end = data.len();
+        while let Some(cookie) = rfind(&data[..end], PYINSTALLER_MAGIC) {
+            end = cookie;
+            if let Some(info) = PythonInfo::carchive(data, cookie) {
+                return Some(info);
+            }
+        }
+        None
+    }
+
+    fn carchive(data: &[u8], cookie: usize) -> Option<PythonInfo> {
+        let package_size = be_u32(data, cookie + 8)? as usize;
+        let toc_offset = be_u32(data, cookie + 12)? as usize;
+        let toc_size = be_u32(data, cookie + 16)? as usize;
+        let python = be_u32(data, cookie + 20)?;
+        // PyInstaller 2.1 and later add the name of the Python library
+        let library = safe_read(data, cookie + PYINSTALLER_COOKIE_SIZE_20, 64);
+        let library = library.split(|&b| b == 0).next()?;
+        let newer = library.len() > 2 && library.iter().all(|b| b.is_ascii_graphic());
+        let cookie_size = match newer {
+            true => PYINSTALLER_COOKIE_SIZE,
+            false => PYINSTALLER_COOKIE_SIZE_20,
+        };
+        let package = (cookie + cookie_size).checked_sub(package_size)?;
+        let toc_start = package.checked_add(toc_offset)?;
+        let toc = data.get(toc_start..toc_start.checked_add(toc_size)?)?;
+        let mut info = PythonInfo::new("PyInstaller", data);
+        info.offset = Some(package as u64);
+        info.python_version = Some(match python {
+            100.. => format!("{}.{}", python / 100, python % 100),
+            _ => format!("{}.{}", python / 10, python % 10),
+        });
+        if newer {
+            info.python_library = Some(String::from_utf8_lossy(library).to_string());
+        }
+        let mut position = 0;
+        while position < toc.len() {
+            let entry_size = be_u32(toc, position)? as usize;
+            if entry_size < PYINSTALLER_TOC_ENTRY_SIZE {
+                return None;
+            }
+            let entry = toc.get(position..position + entry_size)?;
+            position += entry_size;
+            let name = entry[PYINSTALLER_TOC_ENTRY_SIZE..]
+                .split(|&b| b == 0)
+                .next()?;
+            let name = String::from_utf8_lossy(name).to_string();
+            let member = PythonMember {
+                kind: carchive_kind(entry[17]).to_string(),
+                offset: Some(package.checked_add(be_u32(entry, 4)? as usize)? as u64),
+                size: be_u32(entry, 8)? as u64,
+                uncompressed_size: Some(be_u32(entry, 12)? as u64),
+                compressed: entry[16] != 0,
+                name,
+            };
+            match entry[17] {
+                b's' if !PYINSTALLER_BOOTSTRAP_PREFIXES
+                    .iter()
+                    .any(|prefix| member.name.starts_with(prefix)) =>
+                {
+                    info.entry_scripts.push(member.name.clone())
+                }
+                b'z' => {
+                    if let Some(modules) = pyz(data, &member) {
+                        info.details
+                            .push(format!("{} modules in {}", modules.len(), member.name));
+                        info.members.push(member);
+                        info.members.extend(modules);
+                        continue;
+                    }
+                }
+                _ if member.name == PYINSTALLER_CRYPTO_KEY => {
+                    info.details.push("encrypted PYZ".to_string())
+                }
+                _ => {}
+            }
+            info.members.push(member);
+        }
+        Some(info)
+    }
+
+    /// py2exe keeps the marshalled startup scripts in a `PYTHONSCRIPT` resource, the
+    /// library zip is either a separate file or appended to the executable.
+    fn py2exe(pe: (&PE, &[u8]), overlay: Option<&Overlay>) -> Option<PythonInfo> {
+        let script = find_resource(
+            pe.0,
+            pe.1,
+            ResourceId::Name(PY2EXE_SCRIPT_TYPE),
+            ResourceId::Id(1),
+        )?;
+        if le_u32(script, 0)? != PY2EXE_MAGIC {
+            return None;
+        }
+        let mut info = PythonInfo::new("py2exe", pe.1);
+        let code_size = le_u32(script, 12)? as usize;
+        let zip_path = script.get(PY2EXE_HEADER_SIZE..)?;
+        let zip_path = zip_path.split(|&b| b == 0).next()?;
+        let code_start = PY2EXE_HEADER_SIZE + zip_path.len() + 1;
+        let code = safe_read(script, code_start, code_size);
+        info.offset = Some((script.as_ptr() as usize - pe.1.as_ptr() as usize) as u64);
+        info.details
+            .push(format!("optimize {}", le_u32(script, 4)?));
+        if le_u32(script, 8)? != 0 {
+            info.details.push("unbuffered".to_string());
+        }
+        if !zip_path.is_empty() {
+            info.details
+                .push(format!("library {}", String::from_utf8_lossy(zip_path)));
+        }
+        // code objects differ between Python versions, the detected one is tried first
+        let detected = info.python_version.as_deref().and_then(parse_version);
+        let versions = detected
+            .into_iter()
+            .chain([(3, 11), (3, 8), (3, 0), (2, 7)]);
+        info.entry_scripts = versions
+            .filter_map(|version| {
+                let mut reader = MarshalReader::new(code, version);
+                match reader.object(0)? {
+                    Marshal::Sequence(objects) => Some(
+                        objects
+                            .into_iter()
+                            .filter_map(|object| match object {
+                                Marshal::Code { filename } => Some(filename),
+                                _ => None,
+                            })
+                            .collect(),
+                    ),
+                    _ => None,
+                }
+            })
+            .next()
+            .unwrap_or_default();
+        // an empty library path means the zip was appended to the executable
+        if let Some(overlay) = overlay.filter(|_| zip_path.is_empty()) {
+            let library = safe_read(pe.1, overlay.offset as usize, overlay.size as usize);
+            if let Some(listing) = zip::list(library) {
+                info.members = listing
+                    .members
+                    .into_iter()
+                    .map(|member| PythonMember {
+                        kind: "library".to_string(),
+                        offset: None,
+                        size: member.packed_size.unwrap_or(member.size),
+                        uncompressed_size: Some(member.size),
+                        compressed: member.compression != "stored",
+                        name: member.name,
+                    })
+                    .collect();
+            }
+        }
+        Some(info)
+    }
+
+    /// Nuitka compiles to C, onefile binaries carry a payload of the standalone
+    /// distribution, a resource for PE and appended for ELF.
+    fn nuitka(
+        data: &[u8],
+        payload: Option<(usize, &[u8])>,
+        char_size: usize,
+    ) -> Option<PythonInfo> {
+        let payload = payload.filter(|(_, payload)| payload.starts_with(NUITKA_PAYLOAD_MAGIC));
+        if payload.is_none() && !NUITKA_MARKERS.iter().any(|m| find(data, m).is_some()) {
+            return None;
+        }
+        let mut info = PythonInfo::new("Nuitka", data);
+        let Some((offset, payload)) = payload else {
+            return Some(info);
+        };
+        info.offset = Some(offset as u64);
+        match payload.get(2) {
+            Some(b'Y') => info.details.push("zstd compressed payload".to_string()),
+            Some(b'X') => {
+                // the file checksums are optional, the layout that parses is used
+                info.members = [false, true]
+                    .into_iter()
+                    .find_map(|checksums| nuitka_payload(offset, payload, char_size, checksums))
+                    .unwrap_or_default();
+            }
+            _ => {}
+        }
+        Some(info)
+    }
+}
+
+/// Files of an uncompressed Nuitka onefile payload: a name, its size, an optional CRC-32
+/// and the data, up to an empty name.
+fn nuitka_payload(
+    offset: usize,
+    payload: &[u8],
+    char_size: usize,
+    checksums: bool,
+) -> Option<Vec<PythonMember>> {
+    let mut members = vec![];
+    let mut position = 3;
+    loop {
+        let units = payload
+            .get(position..)?
+            .chunks_exact(char_size)
+            .map(|unit| match char_size {
+                2 => u16::from_le_bytes([unit[0], unit[1]]),
+                _ => unit[0] as u16,
+            })
+            .take_while(|&unit| unit != 0)
+            .collect::<Vec<_>>();
+        position += (units.len() + 1) * char_size;
+        if units.is_empty() {
+            return Some(members);
+        }
+        let name = match char_size {
+            2 => String::from_utf16(&units).ok()?,
+            _ => String::from_utf8(units.iter().map(|&unit| unit as u8).collect()).ok()?,
+        };
+        let size = u64::from_le_bytes(payload.get(position..position + 8)?.try_into().ok()?);
+        position += if checksums { 12 } else { 8 };
+        let end = position.checked_add(usize::try_from(size).ok()?)?;
+        payload.get(position..end)?;
+        members.push(PythonMember {
+            name,
+            kind: "file".to_string(),
+            offset: Some((offset + position) as u64),
+            size,
+            uncompressed_size: Some(size),
+            compressed: false,
+        });
+        position = end;
+    }
+}
+
+fn carchive_kind(kind: u8) -> &'static str {
+    match kind {
+        b'b' => "binary",
+        b'd' => "dependency",
+        b'l' => "splash",
+        b'm' => "module",
+        b'M' => "package",
+        b'n' => "symlink",
+        b'o' => "option",
+        b's' => "script",
+        b'x' => "data",
+        b'z' => "PYZ",
+        b'Z' => "zip",
+        _ => "unknown",
+    }
+}
+
+/// Modules of a PYZ archive: magic, `.pyc` magic and the offset of a marshalled table of
+/// contents of `(name, (type, offset, size))`, a dict in old versions.
+fn pyz(data: &[u8], archive: &PythonMember) -> Option<Vec<PythonMember>> {
+    let stored = archive.extract(data).ok()?;
+    if !stored.starts_with(PYZ_MAGIC) {
+        return None;
+    }
+    let toc_offset = be_u32(&stored, 8)? as usize;
+    let version = pyc_version(u16::from_le_bytes(stored.get(4..6)?.try_into().ok()?))?;
+    let mut reader = MarshalReader::new(stored.get(toc_offset..)?, version);
+    let entries = match reader.object(0)? {
+        Marshal::Sequence(entries) => entries
+            .into_iter()
+            .filter_map(|entry| match entry {
+                Marshal::Sequence(mut pair) if pair.len() == 2 => {
+                    let value = pair.pop()?;
+                    Some((pair.pop()?, value))
+                }
+                _ => None,
+            })
+            .collect(),
+        Marshal::Dict(entries) => entries,
+        _ => return None,
+    };
+    // members of a compressed archive cannot be read from the file directly
+    let base = archive.offset.filter(|_| !archive.compressed);
+    entries
+        .into_iter()
+        .map(|(name, value)| {
+            let (Marshal::Str(name), Marshal::Sequence(value)) = (name, value) else {
+                return None;
+            };
+            let [Marshal::Int(kind), Marshal::Int(offset), Marshal::Int(size)] = value.as_slice()
+            else {
+                return None;
+            };
+            Some(PythonMember {
+                name,
+                kind: match kind {
+                    1 => "PYZ package",
+                    2 => "PYZ data",
+                    3 => "PYZ namespace package",
+                    _ => "PYZ module",
+                }
+                .to_string(),
+                offset: match base {
+                    Some(base) => Some(base.checked_add(u64::try_from(*offset).ok()?)?),
+                    None => None,
+                },
+                size: u64::try_from(*size).ok()?,
+                uncompressed_size: None,
+                compressed: true,
+            })
+        })
+        .collect()
+}
+
+/// Python version of a `.pyc` magic number, from the first magic number of each version.
+fn pyc_version(magic: u16) -> Option<(u32, u32)> {
+    const VERSIONS: [(u16, (u32, u32)); 13] = [
+        (3550, (3, 13)),
+        (3500, (3, 12)),
+        (3450, (3, 11)),
+        (3430, (3, 10)),
+        (3420, (3, 9)),
+        (3400, (3, 8)),
+        (3390, (3, 7)),
+        (3360, (3, 6)),
+        (3320, (3, 5)),
+        (3250, (3, 4)),
+        (3190, (3, 3)),
+        (3160, (3, 2)),
+        (3000, (3, 0)),
+    ];
+    match magic {
+        62000.. => Some((2, 7)),
+        _ => VERSIONS
+            .iter()
+            .find(|(first, _)| magic >= *first)
+            .map(|(_, version)| *version),
+    }
+}
+
+/// `python311.dll`, `python27.dll` or `libpython3.11.so.1.0` referenced by the binary.
+fn python_library(data: &[u8]) -> Option<String> {
+    let mut offset = 0;
+    while let Some(pos) = find_ignore_case(&data[offset..], b"python") {
+        let start = offset + pos;
+        offset = start + 1;
+        let Some(end) = data[start..]
+            .iter()
+            .take(32)
+            .position(|&b| !(b.is_ascii_alphanumeric() || b == b'.'))
+            .map(|len| start + len)
+        else {
+            continue;
+        };
+        let mut begin = start;
+        if start >= 3 && data[start - 3..start].eq_ignore_ascii_case(b"lib") {
+            begin = start - 3;
+        }
+        let name = String::from_utf8_lossy(&data[begin..end]).to_string();
+        if library_version(&name).is_some() {
+            return Some(name);
+        }
+    }
+    None
+}
+
+fn library_version(name: &str) -> Option<String> {
+    let lower = name.to_ascii_lowercase();
+    if let Some(rest) = lower.strip_prefix("libpython") {
+        let (major, rest) = rest.split_once('.')?;
+        let minor = rest.split('.').next()?;
+        let numeric = |s: &str| !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit());
+        return (numeric(major) && numeric(minor)).then(|| format!("{}.{}", major, minor));
+    }
+    // `python3.dll` is the stable ABI and does not tell the version
+    let digits = lower.strip_prefix("python")?.strip_suffix(".dll")?;
+    (digits.len() >= 2 && digits.bytes().all(|b| b.is_ascii_digit()))
+        .then(|| format!("{}.{}", &digits[..1], &digits[1..]))
+}
+
+fn parse_version(version: &str) -> Option<(u32, u32)> {
+    let (major, minor) = version.split_once('.')?;
+    Some((major.parse().ok()?, minor.parse().ok()?))
+}
+
+/// Objects of the marshal format this module needs, the rest are `Other`.
+#[derive(Clone, Debug)]
+enum Marshal {
+    Null,
+    Int(i64),
+    Str(String),
+    Sequence(Vec<Marshal>),
+    Dict(Vec<(Marshal, Marshal)>),
+    Code { filename: String },
+    Other,
+}
+
+struct MarshalReader<'a> {
+    data: &'a [u8],
+    position: usize,
+    version: (u32, u32),
+    /// Objects flagged for back references
+    refs: Vec<Marshal>,
+    /// Interned strings of Python 2
+    interned: Vec<String>,
+}
+
+impl MarshalReader<'_> {
+    fn new(data: &[u8], version: (u32, u32)) -> MarshalReader<'_> {
+        MarshalReader {
+            data,
+            position: 0,
+            version,
+            refs: vec![],
+            interned: vec![],
+        }
+    }
+
+    fn bytes(&mut self, count: usize) -> Option<&[u8]> {
+        let end = self.position.checked_add(count)?;
+        let bytes = self.data.get(self.position..end)?;
+        self.position = end;
+        Some(bytes)
+    }
+
+    fn byte(&mut self) -> Option<u8> {
+        Some(self.bytes(1)?[0])
+    }
+
+    fn u32(&mut self) -> Option<u32> {
+        Some(u32::from_le_bytes(self.bytes(4)?.try_into().ok()?))
+    }
+
+    /// A count of items that take at least a byte each.
+    fn count(&mut self, wide: bool) -> Option<usize> {
+        let count = match wide {
+            true => self.u32()? as usize,
+            false => self.byte()? as usize,
+        };
+        (count <= self.data.len() - self.position).then_some(count)
+    }
+
+    fn string(&mut self, wide: bool) -> Option<String> {
+        let size = self.count(wide)?;
+        Some(String::from_utf8_lossy(self.bytes(size)?).to_string())
+    }
+
+    fn object(&mut self, depth: usize) -> Option<Marshal> {
+        if depth > MAX_MARSHAL_DEPTH {
+            return None;
+        }
+        let code = self.byte()?;
+        // the top bit flags objects that later `r` codes refer back to
+        let reference = (code & 0x80 != 0).then(|| {
+            self.refs.push(Marshal::Other);
+            self.refs.len() - 1
+        });
+        let object = match code & 0x7f {
+            b'0' => Marshal::Null,
+            b'N' | b'F' | b'T' | b'.' | b'S' => Marshal::Other,
+            b'i' => Marshal::Int(self.u32()? as i32 as i64),
+            b'I' => Marshal::Int(i64::from_le_bytes(self.bytes(8)?.try_into().ok()?)),
+            b'l' => {
+                // digits of 15 bits, the sign of the count is the sign of the number
+                let count = self.u32()? as i32;
+                let digits = self.bytes(count.unsigned_abs() as usize * 2)?;
+                let value = digits.chunks_exact(2).rev().fold(0i64, |value, digit| {
+                    value
+                        .wrapping_shl(15)
+                        .wrapping_add(u16::from_le_bytes([digit[0], digit[1]]) as i64)
+                });
+                Marshal::Int(if count < 0 {
+                    value.wrapping_neg()
+                } else {
+                    value
+                })
+            }
+            b'g' => {
+                self.bytes(8)?;
+                Marshal::Other
+            }
+            b'y' => {
+                self.bytes(16)?;
+                Marshal::Other
+            }
+            b'f' => {
+                self.string(false)?;
+                Marshal::Other
+            }
+            b'x' => {
+                self.string(false)?;
+                self.string(false)?;
+                Marshal::Other
+            }
+            b't' => {
+                let string = self.string(true)?;
+                self.interned.push(string.clone());
+                Marshal::Str(string)
+            }
+            b's' | b'u' | b'a' | b'A' => Marshal::Str(self.string(true)?),
+            b'z' | b'Z' => Marshal::Str(self.string(false)?),
+            b'R' => {
+                let index = self.u32()? as usize;
+                Marshal::Str(self.interned.get(index)?.clone())
+            }
+            b'r' => {
+                let index = self.u32()? as usize;
+                self.refs.get(index)?.clone()
+            }
+            kind @ (b'(' | b')' | b'[' | b'<' | b'>') => {
+                let count = self.count(kind != b')')?;
+                let items = (0..count)
+                    .map(|_| self.object(depth + 1))
+                    .collect::<Option<Vec<_>>>()?;
+                Marshal::Sequence(items)
+            }
+            b'{' => {
+                let mut items = vec![];
+                loop {
+                    let key = self.object(depth + 1)?;
+                    if matches!(key, Marshal::Null) {
+                        break;
+                    }
+                    items.push((key, self.object(depth + 1)?));
+                }
+                Marshal::Dict(items)
+            }
+            b'c' => self.code(depth)?,
+            _ => return None,
+        };
+        if let Some(index) = reference {
+            // copying containers on every back reference could grow without bound
+            self.refs[index] = match object {
+                Marshal::Sequence(_) | Marshal::Dict(_) => Marshal::Other,
+                _ => object.clone(),
+            };
+        }
+        Some(object)
+    }
+
+    /// Reads a code object and keeps its file name, the layout depends on the version.
+    fn code(&mut self, depth: usize) -> Option<Marshal> {
+        let (integers, before_filename, after_lineno) = match self.version {
+            (2, _) => (4, 6, 1),
+            (3, 0..=7) => (5, 6, 1),
+            (3, 8..=10) => (6, 6, 1),
+            _ => (5, 5, 2),
+        };
+        self.bytes(integers * 4)?;
+        for _ in 0..before_filename {
+            self.object(depth + 1)?;
+        }
+        let Marshal::Str(filename) = self.object(depth + 1)? else {
+            return None;
+        };
+        // name, and the qualified name since 3.11
+        self.object(depth + 1)?;
+        if after_lineno == 2 {
+            self.object(depth + 1)?;
+        }
+        self.u32()?;
+        for _ in 0..after_lineno {
+            self.object(depth + 1)?;
+        }
+        Some(Marshal::Code { filename })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn read(data: &[u8]) -> Option<Marshal> {
+        MarshalReader::new(data, (3, 11)).object(0)
+    }
+
+    #[test]
+    fn marshal_numbers() {
+        assert!(matches!(read(b"i\xfe\xff\xff\xff"), Some(Marshal::Int(-2))));
+        assert!(matches!(
+            read(b"I\x00\x00\x00\x00\x01\x00\x00\x00"),
+            Some(Marshal::Int(0x1_0000_0000))
+        ));
+        // two digits of 15 bits, negative count
+        assert!(matches!(
+            read(b"l\xfe\xff\xff\xff\x01\x00\x02\x00"),
+            Some(Marshal::Int(-0x10001))
+        ));
+        // more digits than fit an i64 wrap instead of overflowing
+        let mut long = b"l\xf0\xff\xff\xff".to_vec();
+        long.extend([0xff, 0x7f].repeat(16));
+        assert!(matches!(read(&long), Some(Marshal::Int(_))));
+    }
+
+    #[test]
+    fn marshal_containers_and_references() {
+        // [('a', 1), r0] with the tuple flagged for references
+        let data = b"[\x02\x00\x00\x00\xa9\x02\xda\x01ai\x01\x00\x00\x00r\x00\x00\x00\x00";
+        let Some(Marshal::Sequence(items)) = read(data) else {
+            panic!("not a list");
+        };
+        assert!(matches!(&items[0], Marshal::Sequence(pair) if pair.len() == 2));
+        // containers are not copied for back references
+        assert!(matches!(items[1], Marshal::Other));
+        // strings are
+        let data = b")\x02\xda\x01ar\x00\x00\x00\x00";
+        let Some(Marshal::Sequence(items)) = read(data) else {
+            panic!("not a tuple");
+        };
+        assert!(matches!(&items[1], Marshal::Str(name) if name == "a"));
+        let data = b"{\xda\x01ai\x01\x00\x00\x000";
+        assert!(matches!(read(data), Some(Marshal::Dict(items)) if items.len() == 1));
+    }
+
+    #[test]
+    fn marshal_truncated() {
+        let data = b"[\x02\x00\x00\x00\xa9\x02\xda\x01ai\x01\x00\x00\x00r\x00\x00\x00\x00";
+        for end in 0..data.len() {
+            assert!(read(&data[..end]).is_none());
+        }
+        // a count larger than the data, a reference that was never made
+        assert!(read(b"[\xff\xff\xff\xff").is_none());
+        assert!(read(b"r\x05\x00\x00\x00").is_none());
+        assert!(read(b"?").is_none());
+    }
+
+    #[test]
+    fn pyz_negative_offset_and_size() {
+        // version 3.11, table of contents after the 12 byte header
+        let mut stored = b"PYZ\0\xa7\x0d\x0d\x0a\x00\x00\x00\x0c".to_vec();
+        stored.extend(b"[\x01\x00\x00\x00)\x02\xda\x01a)\x03i\x00\x00\x00\x00");
+        let toc = stored.len();
+        stored.extend(b"i\x10\x00\x00\x00i\x20\x00\x00\x00");
+        let archive = PythonMember {
+            name: "PYZ-00.pyz".to_string(),
+            kind: "PYZ".to_string(),
+            offset: Some(0),
+            size: stored.len() as u64,
+            uncompressed_size: None,
+            compressed: false,
+        };
+        let members = pyz(&stored, &archive).unwrap();
+        assert_eq!(members[0].offset, Some(0x10));
+        assert_eq!(members[0].size, 0x20);
+        for field in [toc, toc + 5] {
+            let mut stored = stored.clone();
+            stored[field + 1..field + 5].copy_from_slice(&[0xff; 4]);
+            assert!(pyz(&stored, &archive).is_none());
+        }
+    }
+
+    #[test]
+    fn marshal_depth() {
+        let nested = b")\x01".repeat(MAX_MARSHAL_DEPTH + 2);
+        assert!(read(&nested).is_none());
+    }
+}
@@ -0,0 +1,198 @@
+use crate::util::{find, safe_read};
+use flate2::read::ZlibDecoder;
+use goblin::{
+    elf::{section_header::SHT_NOBITS, Elf},
+    pe::PE,
+};
+use serde::{Deserialize, Serialize};
+use std::io::Read;
+
+const RUSTC_PATH: &[u8] = b"/rustc/";
+const REGISTRY_PATHS: [&[u8]; 2] = [b"registry/src/", b"registry\\src\\"];
+/// Strings only the Rust standard library leaves behind
+const RUST_MARKERS: [&[u8]; 2] = [
+    b"called `Option::unwrap()` on a `None` value",
+    b"rust_begin_unwind",
+];
+/// Upper bound for the decompressed `.dep-v0` JSON
+const MAX_DEP_INFO_SIZE: u64 = 8 * 1024 * 1024;
+
+/// Crate referenced by a `.cargo/registry/src/<index>/<name>-<version>/` path.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct RustCrate {
+    pub name: String,
+    pub version: String,
+}
+
+/// Package from the `cargo auditable` dependency list.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct AuditablePackage {
+    pub name: String,
+    pub version: String,
+    /// `crates.io`, `git`, `local` or `registry`
+    #[serde(default)]
+    pub source: String,
+    /// `build` for build dependencies, absent for runtime ones
+    #[serde(default)]
+    pub kind: Option<String>,
+    /// Indexes into the package list
+    #[serde(default)]
+    pub dependencies: Vec<usize>,
+    /// The crate the binary was built from
+    #[serde(default)]
+    pub root: bool,
+}
+
+#[derive(Deserialize)]
+struct DepInfo {
+    packages: Vec<AuditablePackage>,
+}
+
+/// Metadata rustc and cargo leave in a binary.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct RustInfo {
+    /// From `.comment`, e.g. `1.75.0 (82e1608df 2023-12-21)`
+    pub rustc_version: Option<String>,
+    /// Commit hash of rustc, from the `/rustc/<hash>/` paths in panic messages
+    pub rustc_commit: Option<String>,
+    /// Crates from the source paths in panic messages, sorted by name
+    pub crates: Vec<RustCrate>,
+    /// `.dep-v0` section written by `cargo auditable`
+    pub auditable: Option<Vec<AuditablePackage>>,
+}
+
+impl RustInfo {
+    /// Returns `None` for binaries not built by rustc.
+    pub fn from_elf(elf: (&Elf, &[u8])) -> Option<RustInfo> {
+        let section = |name: &str| {
+            elf.0
+                .section_headers
+                .iter()
+                .filter(|sh| sh.sh_type != SHT_NOBITS)
+                .find(|sh| elf.0.shdr_strtab.get_at(sh.sh_name) == Some(name))
+                .map(|sh| safe_read(elf.1, sh.sh_offset as usize, sh.sh_size as usize))
+        };
+        let rustc_version = section(".comment").and_then(|comment| {
+            comment
+                .split(|&b| b == 0)
+                .map(String::from_utf8_lossy)
+                .find_map(|s| s.strip_prefix("rustc version ").map(|v| v.to_string()))
+        });
+        RustInfo::parse(elf.1, rustc_version, section(".dep-v0"))
+    }
+
+    /// Returns `None` for binaries not built by rustc.
+    pub fn from_pe(pe: (&PE, &[u8])) -> Option<RustInfo> {
+        let dep_info =
+            pe.0.sections
+                .iter()
+                .find(|sec| sec.name().unwrap_or_default() == ".dep-v0")
+                .map(|sec| {
+                    safe_read(
+                        pe.1,
+                        sec.pointer_to_raw_data as usize,
+                        sec.size_of_raw_data as usize,
+                    )
+                });
+        RustInfo::parse(pe.1, None, dep_info)
+    }
+
+    fn parse(
+        data: &[u8],
+        rustc_version: Option<String>,
+        dep_info: Option<&[u8]>,
+    ) -> Option<RustInfo> {
+        let info = RustInfo {
+            rustc_version,
+            rustc_commit: rustc_commit(data),
+            crates: crates(data),
+            auditable: dep_info.and_then(auditable),
+        };
+        let rust = info.rustc_version.is_some()
+            || info.rustc_commit.is_some()
+            || info.auditable.is_some()
+            || RUST_MARKERS.iter().any(|m| find(data, m).is_some());
+        rust.then_some(info)
+    }
+}
+
+/// rustc embeds its source paths, `/rustc/<commit>/library/...`, in panic locations.
+fn rustc_commit(data: &[u8]) -> Option<String> {
+    let mut offset = 0;
+    while let Some(pos) = find(&data[offset..], RUSTC_PATH) {
+        let start = offset + pos + RUSTC_PATH.len();
+        offset = start;
+        let Some(hash) = data.get(start..start + 40) else {
+            break;
+        };
+        if hash.iter().all(|b| b.is_ascii_hexdigit())
+            && matches!(data.get(start + 40), Some(b'/') | Some(b'\\'))
+        {
+            return Some(String::from_utf8_lossy(hash).to_string());
+        }
+    }
+    None
+}
+
+/// Crates from `.cargo/registry/src/<index>/<name>-<version>/` paths.
+fn crates(data: &[u8]) -> Vec<RustCrate> {
+    let mut crates: Vec<RustCrate> = vec![];
+    for pattern in REGISTRY_PATHS {
+        let mut offset = 0;
+        while let Some(pos) = find(&data[offset..], pattern) {
+            let start = offset + pos + pattern.len();
+            offset = start;
+            // skip the index directory, e.g. `index.crates.io-6f17d22bba15001f`
+            let path = &data[start..data.len().min(start + 256)];
+            let mut components = path.split(|&b| b == b'/' || b == b'\\');
+            let (Some(_), Some(dir)) = (components.next(), components.next()) else {
+                continue;
+            };
+            let Some(krate) = split_crate_dir(&String::from_utf8_lossy(dir)) else {
+                continue;
+            };
+            if !crates.contains(&krate) {
+                crates.push(krate);
+            }
+        }
+    }
+    crates.sort_by(|a, b| a.name.cmp(&b.name).then(a.version.cmp(&b.version)));
+    crates
+}
+
+/// Splits `serde_json-1.0.128` at the dash that starts the version.
+fn split_crate_dir(dir: &str) -> Option<RustCrate> {
+    let is_version = |v: &str| {
+        let mut parts = v.splitn(3, '.');
+        let numeric =
+            |p: Option<&str>| p.is_some_and(|p| p.starts_with(|c: char| c.is_ascii_digit()));
+        let (major, minor, patch) = (parts.next(), parts.next(), parts.next());
+        numeric(major)
+            && major.is_some_and(|m| m.chars().all(|c| c.is_ascii_digit()))
+            && numeric(minor)
+            && numeric(patch)
+    };
+    dir.match_indices('-')
+        .map(|(i, _)| (&dir[..i], &dir[i + 1..]))
+        .find(|(name, version)| !name.is_empty() && is_version(version))
+        .filter(|(name, _)| {
+            name.chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        })
+        .map(|(name, version)| RustCrate {
+            name: name.to_string(),
+            version: version.to_string(),
+        })
+}
+
+/// Decompresses and decodes the `cargo auditable` zlib compressed JSON.
+fn auditable(bytes: &[u8]) -> Option<Vec<AuditablePackage>> {
+    let mut json = vec![];
+    ZlibDecoder::new(bytes)
+        .take(MAX_DEP_INFO_SIZE)
+        .read_to_end(&mut json)
+        .ok()?;
+    serde_json::from_slice::<DepInfo>(&json)
+        .ok()
+        .map(|info| info.packages)
+}
@@ -1,4 +1,4 @@
-use crate::pe::util::safe_read;
+use crate::util::safe_read;
 use goblin::{elf::SectionHeader, pe::PE};
 use serde::{Deserialize, Serialize};
 
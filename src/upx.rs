@@ -0,0 +1,1086 @@
+use crate::{
+    error::Error,
+    util::{c_string, find_all, le_u32, nul_terminated, read_u32, read_uint},
+};
+use goblin::{
+    container::{Container, Ctx, Endian},
+    elf::{
+        header::{EI_CLASS, EI_DATA, ELFCLASS64, ELFDATA2MSB},
+        program_header::{ProgramHeader, PT_LOAD},
+    },
+    pe::PE,
+};
+use lzma_rs::decompress::{Options, UnpackedSize};
+use serde::{Deserialize, Serialize};
+use std::io::{BufReader, Read};
+
+const UPX_MAGIC: &[u8] = b"UPX!";
+/// Size of the pack header of UPX 1.0 and later
+const PACK_HEADER_SIZE: usize = 32;
+/// `l_info` and `p_info` in front of the first ELF block
+const ELF_INFO_SIZE: usize = 24;
+/// `b_info` in front of each ELF block
+const ELF_BLOCK_INFO_SIZE: usize = 12;
+/// Where the pack headers are searched for
+const HEADER_SEARCH_SIZE: usize = 0x1000;
+/// Unpacked images above this size are rejected rather than allocated
+const MAX_IMAGE_SIZE: usize = 0x4000_0000;
+/// Buffers are reserved for at most this many times the packed size, a larger claimed size
+/// only has to be backed by memory once the data actually decompresses to it
+const MAX_RESERVE_RATIO: usize = 16;
+
+// pack header formats
+const UPX_F_WIN32_PE: u8 = 9;
+const UPX_F_WIN64_PE: u8 = 36;
+
+// compression methods
+const M_NRV2B_LE32: u8 = 2;
+const M_NRV2B_8: u8 = 3;
+const M_NRV2B_LE16: u8 = 4;
+const M_NRV2D_LE32: u8 = 5;
+const M_NRV2D_8: u8 = 6;
+const M_NRV2D_LE16: u8 = 7;
+const M_NRV2E_LE32: u8 = 8;
+const M_NRV2E_8: u8 = 9;
+const M_NRV2E_LE16: u8 = 10;
+const M_LZMA: u8 = 14;
+
+const IMAGE_DIRECTORY_ENTRY_IMPORT: usize = 1;
+const IMAGE_DIRECTORY_ENTRY_RESOURCE: usize = 2;
+const IMAGE_DIRECTORY_ENTRY_SECURITY: usize = 4;
+const IMAGE_DIRECTORY_ENTRY_BASERELOC: usize = 5;
+const IMAGE_FILE_RELOCS_STRIPPED: u16 = 0x0001;
+const SECTION_HEADER_SIZE: usize = 40;
+const IMPORT_DESCRIPTOR_SIZE: usize = 20;
+
+/// Image decompressed from a UPX packed file.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct UpxImage {
+    /// Version of the pack header format
+    pub version: u8,
+    /// UPX output format, e.g. 36 for 64-bit PE
+    pub format: u8,
+    /// `NRV2B`, `NRV2D`, `NRV2E` or `LZMA`
+    pub method: String,
+    /// Id of the filter applied to the code before compression, 0 for none
+    pub filter: u8,
+    pub compressed_size: u64,
+    pub size: u64,
+    /// The unpacked file, parseable like any other binary
+    #[serde(skip)]
+    pub data: Vec<u8>,
+}
+
+impl UpxImage {
+    /// Decompresses a UPX packed PE or ELF file.
+    ///
+    /// PE files are rebuilt with file offsets equal to RVAs, with their imports restored from the
+    /// UPX import table and their base relocations stripped. ELF files are restored byte for byte.
+    pub fn unpack(data: &[u8]) -> Result<UpxImage, Error> {
+        match goblin::Object::parse(data)? {
+            goblin::Object::PE(pe) => UpxImage::from_pe((&pe, data)),
+            goblin::Object::Elf(_) => UpxImage::from_elf(data),
+            _ => Err(Error::UnsupportedFileType),
+        }
+    }
+
+    pub fn from_pe(pe: (&PE, &[u8])) -> Result<UpxImage, Error> {
+        let data = pe.1;
+        let header = PackHeader::find(data).ok_or(Error::BadMagic)?;
+        if header.format != UPX_F_WIN32_PE && header.format != UPX_F_WIN64_PE {
+            return Err(Error::UnsupportedFileType);
+        }
+        let start = header.offset + PACK_HEADER_SIZE;
+        let packed = data
+            .get(start..start + header.c_len as usize)
+            .ok_or(Error::Bounds)?;
+        if adler32(packed) != header.c_adler {
+            return Err(Error::Invalid);
+        }
+        if header.u_len as usize > MAX_IMAGE_SIZE {
+            return Err(Error::Insanity);
+        }
+        let mut obuf = decompress(packed, header.method, header.u_len as usize)?;
+        if adler32(&obuf) != header.u_adler {
+            return Err(Error::Invalid);
+        }
+        let image = rebuild_pe(pe, &header, &mut obuf)?;
+        Ok(UpxImage {
+            version: header.version,
+            format: header.format,
+            method: method_name(header.method).to_string(),
+            filter: header.filter,
+            compressed_size: header.c_len as u64,
+            size: image.len() as u64,
+            data: image,
+        })
+    }
+
+    pub fn from_elf(data: &[u8]) -> Result<UpxImage, Error> {
+        let endian = match data.get(EI_DATA) {
+            Some(&ELFDATA2MSB) => Endian::Big,
+            _ => Endian::Little,
+        };
+        let container = match data.get(EI_CLASS) {
+            Some(&ELFCLASS64) => Container::Big,
+            _ => Container::Little,
+        };
+        let u32_at = |offset: usize| read_u32(data, offset, endian == Endian::Little);
+        // l_info { checksum, magic, lsize, version, format } followed by
+        // p_info { progid, filesize, blocksize } and the blocks
+        let (info, file_size, block_size) =
+            find_all(data, UPX_MAGIC)
+                .take_while(|&pos| pos < HEADER_SEARCH_SIZE)
+                .filter(|&pos| pos >= 4)
+                .map(|pos| pos - 4)
+                .find_map(|info| {
+                    let file_size = u32_at(info + 16)?;
+                    let block_size = u32_at(info + 20)?;
+                    let first = u32_at(info + ELF_INFO_SIZE)?;
+                    (file_size > 0 && block_size > 0 && first > 0 && first <= block_size)
+                        .then_some((info, file_size as usize, block_size as usize))
+                })
+                .ok_or(Error::BadMagic)?;
+        if file_size > MAX_IMAGE_SIZE {
+            return Err(Error::Insanity);
+        }
+
+        let mut stream: Vec<u8> = Vec::with_capacity(reserve(file_size, data.len()));
+        let mut pos = info + ELF_INFO_SIZE;
+        let mut method = 0;
+        let mut filter = 0;
+        loop {
+            let sz_unc = u32_at(pos).ok_or(Error::Bounds)? as usize;
+            let sz_cpr = u32_at(pos + 4).ok_or(Error::Bounds)? as usize;
+            if sz_unc == 0 {
+                break;
+            }
+            let [b_method, b_ftid, b_cto8] = [pos + 8, pos + 9, pos + 10]
+                .map(|offset| data.get(offset).copied().unwrap_or_default());
+            if sz_unc > block_size || sz_cpr > sz_unc || stream.len() + sz_unc > file_size {
+                return Err(Error::Invalid);
+            }
+            let packed = data
+                .get(pos + ELF_BLOCK_INFO_SIZE..pos + ELF_BLOCK_INFO_SIZE + sz_cpr)
+                .ok_or(Error::Bounds)?;
+            let mut block = if sz_cpr < sz_unc {
+                method = b_method;
+                decompress(packed, b_method, sz_unc)?
+            } else {
+                packed.to_vec()
+            };
+            if b_ftid != 0 {
+                filter = b_ftid;
+                // the ELF and program headers at the start of the file are never filtered
+                let skip = if stream.is_empty() {
+                    elf_headers_size(&block, container, endian)
+                } else {
+                    0
+                };
+                unfilter(
+                    block.get_mut(skip..).ok_or(Error::Bounds)?,
+                    b_ftid,
+                    b_cto8,
+                    0,
+                )?;
+            }
+            stream.append(&mut block);
+            pos += ELF_BLOCK_INFO_SIZE + sz_cpr;
+        }
+        let image = rebuild_elf(&stream, file_size, container, endian)?;
+        Ok(UpxImage {
+            version: data.get(info + 10).copied().unwrap_or_default(),
+            format: data.get(info + 11).copied().unwrap_or_default(),
+            method: method_name(method).to_string(),
+            filter,
+            compressed_size: (pos - info) as u64,
+            size: image.len() as u64,
+            data: image,
+        })
+    }
+}
+
+/// The `UPX!` header of PE files, in the file header area.
+#[derive(Clone, Debug, Default)]
+struct PackHeader {
+    offset: usize,
+    version: u8,
+    format: u8,
+    method: u8,
+    u_adler: u32,
+    c_adler: u32,
+    u_len: u32,
+    c_len: u32,
+    filter: u8,
+    filter_cto: u8,
+}
+
+impl PackHeader {
+    fn find(data: &[u8]) -> Option<PackHeader> {
+        find_all(data, UPX_MAGIC)
+            .take_while(|&pos| pos < HEADER_SEARCH_SIZE)
+            .find_map(|pos| PackHeader::parse(data, pos))
+    }
+
+    fn parse(data: &[u8], offset: usize) -> Option<PackHeader> {
+        let header = data.get(offset..offset + PACK_HEADER_SIZE)?;
+        let checksum = header[4..PACK_HEADER_SIZE - 1]
+            .iter()
+            .map(|&b| b as u32)
+            .sum::<u32>()
+            % 251;
+        if header[4] < 10 || checksum != header[PACK_HEADER_SIZE - 1] as u32 {
+            return None;
+        }
+        let le32 = |at: usize| le_u32(header, at);
+        Some(PackHeader {
+            offset,
+            version: header[4],
+            format: header[5],
+            method: header[6],
+            u_adler: le32(8)?,
+            c_adler: le32(12)?,
+            u_len: le32(16)?,
+            c_len: le32(20)?,
+            filter: header[28],
+            filter_cto: header[29],
+        })
+    }
+}
+
+fn method_name(method: u8) -> &'static str {
+    match method {
+        M_NRV2B_LE32 | M_NRV2B_8 | M_NRV2B_LE16 => "NRV2B",
+        M_NRV2D_LE32 | M_NRV2D_8 | M_NRV2D_LE16 => "NRV2D",
+        M_NRV2E_LE32 | M_NRV2E_8 | M_NRV2E_LE16 => "NRV2E",
+        M_LZMA => "LZMA",
+        _ => "stored",
+    }
+}
+
+fn decompress(src: &[u8], method: u8, u_len: usize) -> Result<Vec<u8>, Error> {
+    let (variant, width) = match method {
+        M_NRV2B_LE32 => (Nrv::B, 32),
+        M_NRV2B_8 => (Nrv::B, 8),
+        M_NRV2B_LE16 => (Nrv::B, 16),
+        M_NRV2D_LE32 => (Nrv::D, 32),
+        M_NRV2D_8 => (Nrv::D, 8),
+        M_NRV2D_LE16 => (Nrv::D, 16),
+        M_NRV2E_LE32 => (Nrv::E, 32),
+        M_NRV2E_8 => (Nrv::E, 8),
+        M_NRV2E_LE16 => (Nrv::E, 16),
+        M_LZMA => return lzma(src, u_len),
+        _ => return Err(Error::UnsupportedCompression),
+    };
+    let out = nrv(src, variant, width, u_len)?;
+    if out.len() != u_len {
+        return Err(Error::Invalid);
+    }
+    Ok(out)
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Nrv {
+    B,
+    D,
+    E,
+}
+
+/// Reads the flag bits of the NRV formats, most significant bit first, from 8, 16 or 32 bit
+/// little endian words interleaved with the literal bytes.
+struct BitReader<'a> {
+    src: &'a [u8],
+    pos: usize,
+    bits: u32,
+    count: u32,
+    width: u32,
+}
+
+impl BitReader<'_> {
+    fn bit(&mut self) -> Result<u32, Error> {
+        if self.count == 0 {
+            let size = self.width as usize / 8;
+            let word = self
+                .src
+                .get(self.pos..self.pos + size)
+                .ok_or(Error::Bounds)?;
+            self.bits = word.iter().rev().fold(0, |bits, &b| (bits << 8) | b as u32);
+            self.pos += size;
+            self.count = self.width;
+        }
+        self.count -= 1;
+        Ok((self.bits >> self.count) & 1)
+    }
+
+    fn byte(&mut self) -> Result<u8, Error> {
+        let byte = *self.src.get(self.pos).ok_or(Error::Bounds)?;
+        self.pos += 1;
+        Ok(byte)
+    }
+
+    /// Elias gamma style number, `value*2 + bit` until a stop bit.
+    fn gamma(&mut self, mut value: u32) -> Result<u32, Error> {
+        loop {
+            value = value.checked_mul(2).ok_or(Error::Overflow)? + self.bit()?;
+            if self.bit()? == 1 {
+                return Ok(value);
+            }
+        }
+    }
+}
+
+/// The NRV2B, NRV2D and NRV2E decompressors of the UCL library.
+fn nrv(src: &[u8], variant: Nrv, width: u32, u_len: usize) -> Result<Vec<u8>, Error> {
+    let mut reader = BitReader {
+        src,
+        pos: 0,
+        bits: 0,
+        count: 0,
+        width,
+    };
+    let mut dst: Vec<u8> = Vec::with_capacity(reserve(u_len, src.len()));
+    let mut last_offset: u32 = 1;
+    loop {
+        while reader.bit()? == 1 {
+            if dst.len() >= u_len {
+                return Err(Error::Overflow);
+            }
+            dst.push(reader.byte()?);
+        }
+        let mut offset: u32 = 1;
+        match variant {
+            Nrv::B => offset = reader.gamma(offset)?,
+            Nrv::D | Nrv::E => loop {
+                offset = offset.checked_mul(2).ok_or(Error::Overflow)? + reader.bit()?;
+                if reader.bit()? == 1 {
+                    break;
+                }
+                offset = (offset - 1).checked_mul(2).ok_or(Error::Overflow)? + reader.bit()?;
+            },
+        }
+        if offset > 0x0100_0002 {
+            return Err(Error::Invalid);
+        }
+        let mut length: u32 = 0;
+        if offset == 2 {
+            offset = last_offset;
+            if variant != Nrv::B {
+                length = reader.bit()?;
+            }
+        } else {
+            let value = (offset - 3) * 256 + reader.byte()? as u32;
+            if value == u32::MAX {
+                break;
+            }
+            offset = match variant {
+                Nrv::B => value + 1,
+                Nrv::D | Nrv::E => {
+                    length = !value & 1;
+                    (value >> 1) + 1
+                }
+            };
+            last_offset = offset;
+        }
+        length = match variant {
+            Nrv::B | Nrv::D => {
+                if variant == Nrv::B {
+                    length = reader.bit()?;
+                }
+                match length * 2 + reader.bit()? {
+                    0 => reader.gamma(1)? + 2,
+                    length => length,
+                }
+            }
+            Nrv::E => {
+                if length != 0 {
+                    1 + reader.bit()?
+                } else if reader.bit()? == 1 {
+                    3 + reader.bit()?
+                } else {
+                    reader.gamma(1)? + 3
+                }
+            }
+        };
+        let far = if variant == Nrv::B { 0xd00 } else { 0x500 };
+        length += (offset > far) as u32;
+        let offset = offset as usize;
+        let count = length as usize + 1;
+        if offset == 0 || offset > dst.len() || dst.len() + count > u_len {
+            return Err(Error::Invalid);
+        }
+        let start = dst.len() - offset;
+        // the match can overlap the bytes it produces
+        for i in 0..count {
+            dst.push(dst[start + i]);
+        }
+    }
+    Ok(dst)
+}
+
+/// LZMA stream behind a two byte header with the `lc`, `lp` and `pb` properties.
+fn lzma(src: &[u8], u_len: usize) -> Result<Vec<u8>, Error> {
+    let (Some(&b0), Some(&b1), Some(stream)) = (src.first(), src.get(1), src.get(2..)) else {
+        return Err(Error::Bounds);
+    };
+    let (pb, lp, lc) = (b0 & 7, b1 >> 4, b1 & 15);
+    if pb >= 5 || lp >= 5 || lc >= 9 || (b0 >> 3) != lc + lp {
+        return Err(Error::Invalid);
+    }
+    // rebuild the header lzma-rs expects: properties and dictionary size
+    let mut header = vec![(pb * 5 + lp) * 9 + lc];
+    header.extend_from_slice(&(u_len.max(4096) as u32).to_le_bytes());
+    let mut input = BufReader::new(header.as_slice().chain(stream));
+    let mut out = Vec::with_capacity(reserve(u_len, src.len()));
+    lzma_rs::lzma_decompress_with_options(
+        &mut input,
+        &mut out,
+        &Options {
+            unpacked_size: UnpackedSize::UseProvided(Some(u_len as u64)),
+            memlimit: None,
+            allow_incomplete: false,
+        },
+    )
+    .map_err(|_| Error::Invalid)?;
+    Ok(out)
+}
+
+/// Reverts the x86 call trick filters, which store the targets of `call`, `jmp` and `jcc`
+/// rel32 operands as big endian offsets tagged with the `cto` byte.
+fn unfilter(buf: &mut [u8], id: u8, cto: u8, addvalue: u32) -> Result<(), Error> {
+    let (calls, jumps, jcc) = match id {
+        0 => return Ok(()),
+        0x24 => (true, false, false),
+        0x25 => (false, true, false),
+        0x26 => (true, true, false),
+        0x49 => (true, true, true),
+        _ => return Err(Error::UnsupportedCompression),
+    };
+    let mut last = 0;
+    let mut ic = 0;
+    while ic + 5 < buf.len() {
+        let op = buf[ic];
+        let matched = (calls && op == 0xE8)
+            || (jumps && op == 0xE9)
+            || (jcc && ic > 0 && ic != last && buf[ic - 1] == 0x0F && (0x80..=0x8F).contains(&op));
+        if matched && buf[ic + 1] == cto {
+            let stored = u32::from_be_bytes([buf[ic + 1], buf[ic + 2], buf[ic + 3], buf[ic + 4]]);
+            let relative = (stored & 0x00FF_FFFF).wrapping_sub(ic as u32 + 1 + addvalue);
+            buf[ic + 1..ic + 5].copy_from_slice(&relative.to_le_bytes());
+            ic += 5;
+            last = ic;
+        } else {
+            ic += 1;
+        }
+    }
+    Ok(())
+}
+
+/// UPX's variant of adler32, seeded with 1.
+fn adler32(data: &[u8]) -> u32 {
+    let (mut a, mut b) = (1u32, 0u32);
+    for chunk in data.chunks(5552) {
+        for &byte in chunk {
+            a += byte as u32;
+            b += a;
+        }
+        a %= 65521;
+        b %= 65521;
+    }
+    (b << 16) | a
+}
+
+enum Import {
+    Name(String),
+    Ordinal(u16),
+}
+
+/// Lays the decompressed sections out at their RVAs behind the original headers.
+fn rebuild_pe(pe: (&PE, &[u8]), header: &PackHeader, obuf: &mut [u8]) -> Result<Vec<u8>, Error> {
+    let (packed, data) = pe;
+    let pe64 = header.format == UPX_F_WIN64_PE;
+    let nt_size = if pe64 { 264 } else { 248 };
+    let thunk_size = if pe64 { 8 } else { 4 };
+    let le32 = |buf: &[u8], at: usize| le_u32(buf, at).ok_or(Error::Bounds);
+
+    // the original NT headers and section table follow the image
+    let extra = le32(obuf, obuf.len().checked_sub(4).ok_or(Error::Bounds)?)? as usize;
+    let mut nt = obuf
+        .get(extra..extra + nt_size)
+        .ok_or(Error::Bounds)?
+        .to_vec();
+    let sections = u16::from_le_bytes([nt[6], nt[7]]) as usize;
+    let table = extra + nt_size;
+    let mut section_table = obuf
+        .get(table..table + sections * SECTION_HEADER_SIZE)
+        .ok_or(Error::Bounds)?
+        .to_vec();
+    if sections == 0 {
+        return Err(Error::Invalid);
+    }
+    let rva_min = le32(&section_table, 12)?;
+    let optional = 24;
+    let section_alignment = le32(&nt, optional + 32)?.max(0x200);
+
+    if header.filter != 0 {
+        let code_base = le32(&nt, optional + 20)?;
+        let code_size = le32(&nt, optional + 4)? as usize;
+        let start = code_base.checked_sub(rva_min).ok_or(Error::Bounds)?;
+        let code = obuf.get_mut(start as usize..).ok_or(Error::Bounds)?;
+        let len = code.len().min(code_size);
+        unfilter(&mut code[..len], header.filter, header.filter_cto, start)?;
+    }
+
+    // the image as the UPX stub sees it after decompression
+    let packed_end = packed
+        .sections
+        .iter()
+        .map(|sec| sec.virtual_address as u64 + sec.virtual_size.max(sec.size_of_raw_data) as u64)
+        .max()
+        .unwrap_or_default();
+    let original_end = (0..sections)
+        .map(|i| {
+            let entry = &section_table[i * SECTION_HEADER_SIZE..];
+            let vaddr = le32(entry, 12).unwrap_or_default() as u64;
+            let size = le32(entry, 8)
+                .unwrap_or_default()
+                .max(le32(entry, 16).unwrap_or_default());
+            vaddr + size as u64
+        })
+        .max()
+        .unwrap_or_default();
+    let image_size = align(packed_end.max(original_end), section_alignment as u64);
+    if image_size > MAX_IMAGE_SIZE as u64 {
+        return Err(Error::Insanity);
+    }
+    let mut image = vec![0u8; image_size as usize];
+    for sec in packed.sections.iter().skip(2) {
+        let raw = crate::util::safe_read(
+            data,
+            sec.pointer_to_raw_data as usize,
+            sec.size_of_raw_data as usize,
+        );
+        let start = (sec.virtual_address as usize).min(image.len());
+        let end = (start + raw.len()).min(image.len());
+        image[start..end].copy_from_slice(&raw[..end - start]);
+    }
+    let start = rva_min as usize;
+    let end = start + extra;
+    image
+        .get_mut(start..end)
+        .ok_or(Error::Bounds)?
+        .copy_from_slice(&obuf[..extra]);
+
+    let directory = |nt: &[u8], index: usize| {
+        let at = optional + if pe64 { 112 } else { 96 } + index * 8;
+        (
+            le32(nt, at).unwrap_or_default(),
+            le32(nt, at + 4).unwrap_or_default(),
+        )
+    };
+    let set_directory = |nt: &mut [u8], index: usize, rva: u32, size: u32| {
+        let at = optional + if pe64 { 112 } else { 96 } + index * 8;
+        nt[at..at + 4].copy_from_slice(&rva.to_le_bytes());
+        nt[at + 4..at + 8].copy_from_slice(&size.to_le_bytes());
+    };
+
+    // imports, from the table the stub resolves
+    let mut libraries: Vec<(String, u32, Vec<Import>)> = vec![];
+    let (import_rva, import_size) = directory(&nt, IMAGE_DIRECTORY_ENTRY_IMPORT);
+    if import_rva != 0 && import_size as usize > IMPORT_DESCRIPTOR_SIZE {
+        let packed_imports = packed
+            .header
+            .optional_header
+            .and_then(|oh| oh.data_directories.get_import_table().copied())
+            .map(|dd| dd.virtual_address as usize)
+            .ok_or(Error::Invalid)?;
+        let mut p = le32(obuf, table + sections * SECTION_HEADER_SIZE)? as usize;
+        while le32(obuf, p)? != 0 {
+            let name = c_string(
+                nul_terminated(&image, packed_imports + le32(obuf, p)? as usize)
+                    .ok_or(Error::Bounds)?,
+            );
+            let iat = le32(obuf, p + 4)?
+                .checked_add(rva_min)
+                .ok_or(Error::Overflow)?;
+            p += 8;
+            let mut imports = vec![];
+            loop {
+                let marker = *obuf.get(p).ok_or(Error::Bounds)?;
+                p += 1;
+                match marker {
+                    0 => break,
+                    0xFF => {
+                        let ordinal = obuf.get(p..p + 2).ok_or(Error::Bounds)?;
+                        imports.push(Import::Ordinal(u16::from_le_bytes([
+                            ordinal[0], ordinal[1],
+                        ])));
+                        p += 2;
+                    }
+                    0xFE => {
+                        // a function the stub imports itself, the offset of its thunk
+                        let thunk = packed_imports + le32(obuf, p)? as usize;
+                        let hint_name = le32(&image, thunk)? as usize;
+                        imports.push(Import::Name(c_string(
+                            nul_terminated(&image, hint_name + 2).ok_or(Error::Bounds)?,
+                        )));
+                        p += 4;
+                    }
+                    _ => {
+                        let name = c_string(nul_terminated(obuf, p).ok_or(Error::Bounds)?);
+                        p += name.len() + 1;
+                        imports.push(Import::Name(name));
+                    }
+                }
+            }
+            libraries.push((name, iat, imports));
+        }
+    }
+
+    // new import directory in a section of its own behind the image
+    let idata_rva = image.len() as u32;
+    let mut idata: Vec<u8> = vec![0; (libraries.len() + 1) * IMPORT_DESCRIPTOR_SIZE];
+    for (index, (name, iat, imports)) in libraries.iter().enumerate() {
+        let lookup = idata.len();
+        idata.resize(lookup + (imports.len() + 1) * thunk_size, 0);
+        for (i, import) in imports.iter().enumerate() {
+            let thunk = match import {
+                Import::Ordinal(ordinal) => {
+                    if pe64 {
+                        (1u64 << 63) | *ordinal as u64
+                    } else {
+                        (1u64 << 31) | *ordinal as u64
+                    }
+                }
+                Import::Name(name) => {
+                    let hint_name = idata_rva as u64 + idata.len() as u64;
+                    idata.extend_from_slice(&[0, 0]);
+                    idata.extend_from_slice(name.as_bytes());
+                    idata.push(0);
+                    if idata.len() % 2 == 1 {
+                        idata.push(0);
+                    }
+                    hint_name
+                }
+            };
+            let bytes = &thunk.to_le_bytes()[..thunk_size];
+            let at = lookup + i * thunk_size;
+            idata[at..at + thunk_size].copy_from_slice(bytes);
+            let slot = *iat as usize + i * thunk_size;
+            if let Some(slot) = image.get_mut(slot..slot + thunk_size) {
+                slot.copy_from_slice(bytes);
+            }
+        }
+        let name_rva = idata_rva + idata.len() as u32;
+        idata.extend_from_slice(name.as_bytes());
+        idata.push(0);
+        let descriptor = index * IMPORT_DESCRIPTOR_SIZE;
+        idata[descriptor..descriptor + 4]
+            .copy_from_slice(&(idata_rva + lookup as u32).to_le_bytes());
+        idata[descriptor + 12..descriptor + 16].copy_from_slice(&name_rva.to_le_bytes());
+        idata[descriptor + 16..descriptor + 20].copy_from_slice(&iat.to_le_bytes());
+    }
+    // the packed resource directory and the resources left uncompressed, without the UPX
+    // section names so the image is not detected as packed again
+    for sec in packed.sections.iter().skip(2) {
+        let size = align(
+            sec.virtual_size.max(sec.size_of_raw_data) as u64,
+            section_alignment as u64,
+        ) as u32;
+        let name: &[u8] = if sec.name.starts_with(b"UPX") || sec.name.starts_with(b".UPX") {
+            b""
+        } else {
+            &sec.name
+        };
+        section_table.extend_from_slice(&section_header(
+            name,
+            sec.virtual_address,
+            size,
+            sec.characteristics,
+        ));
+    }
+    if !libraries.is_empty() {
+        set_directory(
+            &mut nt,
+            IMAGE_DIRECTORY_ENTRY_IMPORT,
+            idata_rva,
+            ((libraries.len() + 1) * IMPORT_DESCRIPTOR_SIZE) as u32,
+        );
+        let idata_size = align(idata.len() as u64, section_alignment as u64) as usize;
+        image.extend_from_slice(&idata);
+        image.resize(idata_rva as usize + idata_size, 0);
+        section_table.extend_from_slice(&section_header(
+            b".imports",
+            idata_rva,
+            idata_size as u32,
+            0xC000_0040,
+        ));
+    }
+
+    let resources = packed
+        .header
+        .optional_header
+        .and_then(|oh| oh.data_directories.get_resource_table().copied());
+    if let Some(resources) = resources {
+        set_directory(
+            &mut nt,
+            IMAGE_DIRECTORY_ENTRY_RESOURCE,
+            resources.virtual_address,
+            resources.size,
+        );
+    }
+    set_directory(&mut nt, IMAGE_DIRECTORY_ENTRY_SECURITY, 0, 0);
+    // UPX keeps the relocations in a format of its own
+    set_directory(&mut nt, IMAGE_DIRECTORY_ENTRY_BASERELOC, 0, 0);
+    let characteristics = u16::from_le_bytes([nt[22], nt[23]]) | IMAGE_FILE_RELOCS_STRIPPED;
+    nt[22..24].copy_from_slice(&characteristics.to_le_bytes());
+
+    // file offsets equal to RVAs
+    for i in 0..sections {
+        let entry = &mut section_table[i * SECTION_HEADER_SIZE..(i + 1) * SECTION_HEADER_SIZE];
+        let vaddr = le32(entry, 12)?;
+        let size = le32(entry, 8)?.max(le32(entry, 16)?);
+        entry[16..20]
+            .copy_from_slice(&(align(size as u64, section_alignment as u64) as u32).to_le_bytes());
+        entry[20..24].copy_from_slice(&vaddr.to_le_bytes());
+        entry[24..36].fill(0);
+    }
+    let total_sections = section_table.len() / SECTION_HEADER_SIZE;
+    nt[6..8].copy_from_slice(&(total_sections as u16).to_le_bytes());
+    nt[optional + 36..optional + 40].copy_from_slice(&section_alignment.to_le_bytes());
+    nt[optional + 56..optional + 60].copy_from_slice(&(image.len() as u32).to_le_bytes());
+    nt[optional + 64..optional + 68].fill(0);
+
+    let dos_size = packed.header.dos_header.pe_pointer as usize;
+    let headers_size = dos_size + nt.len() + section_table.len();
+    if headers_size > rva_min as usize {
+        return Err(Error::Insanity);
+    }
+    nt[optional + 60..optional + 64].copy_from_slice(&rva_min.to_le_bytes());
+    image[..dos_size].copy_from_slice(data.get(..dos_size).ok_or(Error::Bounds)?);
+    image[dos_size..dos_size + nt.len()].copy_from_slice(&nt);
+    image[dos_size + nt.len()..headers_size].copy_from_slice(&section_table);
+    Ok(image)
+}
+
+fn section_header(name: &[u8], rva: u32, size: u32, characteristics: u32) -> [u8; 40] {
+    let mut entry = [0u8; SECTION_HEADER_SIZE];
+    let len = name.len().min(8);
+    entry[..len].copy_from_slice(&name[..len]);
+    entry[8..12].copy_from_slice(&size.to_le_bytes());
+    entry[12..16].copy_from_slice(&rva.to_le_bytes());
+    entry[16..20].copy_from_slice(&size.to_le_bytes());
+    entry[20..24].copy_from_slice(&rva.to_le_bytes());
+    entry[36..40].copy_from_slice(&characteristics.to_le_bytes());
+    entry
+}
+
+/// Puts the `PT_LOAD` extents and the gaps between them back at their file offsets, in the
+/// order UPX compressed them.
+fn rebuild_elf(
+    stream: &[u8],
+    file_size: usize,
+    container: Container,
+    endian: Endian,
+) -> Result<Vec<u8>, Error> {
+    let header = goblin::elf::Elf::parse_header(stream)?;
+    let ctx = Ctx::new(container, endian);
+    let program_headers = ProgramHeader::parse(
+        stream,
+        header.e_phoff as usize,
+        header.e_phnum as usize,
+        ctx,
+    )?;
+    let loads: Vec<&ProgramHeader> = program_headers
+        .iter()
+        .filter(|ph| ph.p_type == PT_LOAD)
+        .collect();
+    let mut extents: Vec<(usize, usize)> = loads
+        .iter()
+        .map(|ph| (ph.p_offset as usize, ph.p_filesz as usize))
+        .collect();
+    for ph in loads.iter() {
+        let end = ph
+            .p_offset
+            .checked_add(ph.p_filesz)
+            .ok_or(Error::Overflow)? as usize;
+        let next = loads
+            .iter()
+            .map(|other| other.p_offset as usize)
+            .filter(|&offset| offset >= end)
+            .min()
+            .unwrap_or(file_size)
+            .min(file_size);
+        if next > end {
+            extents.push((end, next - end));
+        }
+    }
+    let mut image = vec![0u8; file_size];
+    let mut consumed = 0usize;
+    for (offset, size) in extents {
+        let Some(chunk) = stream.get(consumed..consumed.saturating_add(size)) else {
+            break;
+        };
+        let end = offset.checked_add(size).ok_or(Error::Overflow)?;
+        image
+            .get_mut(offset..end)
+            .ok_or(Error::Bounds)?
+            .copy_from_slice(chunk);
+        consumed += size;
+    }
+    if consumed != stream.len() {
+        return Err(Error::Invalid);
+    }
+    Ok(image)
+}
+
+fn elf_headers_size(data: &[u8], container: Container, endian: Endian) -> usize {
+    let read = |at: usize, size: usize| {
+        read_uint(data, at, size, endian == Endian::Little).map(|v| v as usize)
+    };
+    let (phoff, phentsize, phnum) = match container {
+        Container::Big => (read(32, 8), read(54, 2), read(56, 2)),
+        Container::Little => (read(28, 4), read(42, 2), read(44, 2)),
+    };
+    match (phoff, phentsize, phnum) {
+        (Some(phoff), Some(phentsize), Some(phnum)) => phoff + phentsize * phnum,
+        _ => 0,
+    }
+}
+
+/// Capacity to reserve for `size` bytes decompressed from `packed_size` bytes.
+fn reserve(size: usize, packed_size: usize) -> usize {
+    size.min(packed_size.saturating_mul(MAX_RESERVE_RATIO))
+}
+
+fn align(value: u64, alignment: u64) -> u64 {
+    value.div_ceil(alignment) * alignment
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Writes NRV streams: flag bits in words of `width` bits between the literal bytes.
+    struct BitWriter {
+        out: Vec<u8>,
+        word: usize,
+        count: u32,
+        width: u32,
+    }
+
+    impl BitWriter {
+        fn new(width: u32) -> BitWriter {
+            BitWriter {
+                out: vec![],
+                word: 0,
+                count: 0,
+                width,
+            }
+        }
+
+        fn bit(&mut self, bit: u32) {
+            if self.count == 0 {
+                self.word = self.out.len();
+                self.out.resize(self.word + self.width as usize / 8, 0);
+                self.count = self.width;
+            }
+            self.count -= 1;
+            let index = self.word + self.count as usize / 8;
+            self.out[index] |= (bit as u8) << (self.count % 8);
+        }
+
+        fn byte(&mut self, byte: u8) {
+            self.out.push(byte);
+        }
+
+        /// Inverse of `BitReader::gamma` starting from 1.
+        fn gamma(&mut self, value: u32) {
+            let bits = 31 - value.leading_zeros();
+            for index in (0..bits).rev() {
+                self.bit((value >> index) & 1);
+                self.bit((index == 0) as u32);
+            }
+        }
+
+        /// Offset prefix of NRV2D and NRV2E, which subtracts one before every continuation.
+        fn offset(&mut self, variant: Nrv, value: u32) {
+            if variant == Nrv::B {
+                return self.gamma(value);
+            }
+            let mut rounds = vec![];
+            let mut value_before = value >> 1;
+            while value_before > 1 {
+                let doubled = (value_before >> 1) + 1;
+                rounds.push((doubled & 1, value_before & 1));
+                value_before = doubled >> 1;
+            }
+            for &(first, second) in rounds.iter().rev() {
+                self.bit(first);
+                self.bit(0);
+                self.bit(second);
+            }
+            self.bit(value & 1);
+            self.bit(1);
+        }
+
+        fn literal(&mut self, byte: u8) {
+            self.bit(1);
+            self.byte(byte);
+        }
+
+        /// A match with a new offset `distance` bytes back copying `count` bytes.
+        fn copy(&mut self, variant: Nrv, distance: u32, count: u32) {
+            self.bit(0);
+            let length = count - 1;
+            // high bit of lengths up to 3, longer ones follow as a gamma number
+            let high = if length < 4 { length >> 1 } else { 0 };
+            let value = match variant {
+                Nrv::B => distance - 1,
+                Nrv::D => (distance - 1) * 2 + (high == 0) as u32,
+                Nrv::E => (distance - 1) * 2 + (length > 2) as u32,
+            };
+            self.offset(variant, (value >> 8) + 3);
+            self.byte(value as u8);
+            match variant {
+                Nrv::B | Nrv::D => {
+                    if variant == Nrv::B {
+                        self.bit(high);
+                    }
+                    if length < 4 {
+                        self.bit(length & 1);
+                    } else {
+                        self.bit(0);
+                        self.gamma(length - 2);
+                    }
+                }
+                Nrv::E => match length {
+                    1 | 2 => self.bit(length - 1),
+                    3 | 4 => {
+                        self.bit(1);
+                        self.bit(length - 3);
+                    }
+                    _ => {
+                        self.bit(0);
+                        self.gamma(length - 3);
+                    }
+                },
+            }
+        }
+
+        fn end(mut self, variant: Nrv) -> Vec<u8> {
+            self.bit(0);
+            self.offset(variant, 0x0100_0002);
+            self.byte(0xff);
+            self.out
+        }
+    }
+
+    const VARIANTS: [Nrv; 3] = [Nrv::B, Nrv::D, Nrv::E];
+
+    fn stream(variant: Nrv, width: u32, count: u32) -> Vec<u8> {
+        let mut writer = BitWriter::new(width);
+        for &byte in b"abc" {
+            writer.literal(byte);
+        }
+        writer.copy(variant, 3, count);
+        writer.literal(b'!');
+        writer.end(variant)
+    }
+
+    fn expected(count: usize) -> Vec<u8> {
+        let mut expected: Vec<u8> = b"abc".iter().copied().cycle().take(3 + count).collect();
+        expected.push(b'!');
+        expected
+    }
+
+    #[test]
+    fn nrv_literals_and_matches() {
+        for variant in VARIANTS {
+            for width in [8, 16, 32] {
+                // short lengths from the flag bits, longer ones from a gamma number
+                for count in [2, 3, 4, 5, 6, 40] {
+                    let src = stream(variant, width, count);
+                    let expected = expected(count as usize);
+                    assert_eq!(
+                        nrv(&src, variant, width, expected.len()).ok(),
+                        Some(expected)
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn nrv_repeated_offset() {
+        for variant in VARIANTS {
+            let mut writer = BitWriter::new(8);
+            for &byte in b"ab" {
+                writer.literal(byte);
+            }
+            writer.copy(variant, 2, 2);
+            writer.literal(b'c');
+            // offset 2 reuses the last offset, NRV2D and NRV2E then read the length bit
+            writer.bit(0);
+            writer.offset(variant, 2);
+            match variant {
+                Nrv::B | Nrv::D => {
+                    writer.bit(0);
+                    writer.bit(1);
+                }
+                Nrv::E => {
+                    writer.bit(1);
+                    writer.bit(0);
+                }
+            }
+            let src = writer.end(variant);
+            assert_eq!(nrv(&src, variant, 8, 7).ok(), Some(b"ababcbc".to_vec()));
+        }
+    }
+
+    #[test]
+    fn nrv_truncated() {
+        for variant in VARIANTS {
+            let src = stream(variant, 32, 40);
+            for end in 0..src.len() {
+                assert!(nrv(&src[..end], variant, 32, 44).is_err());
+            }
+        }
+    }
+
+    #[test]
+    fn nrv_bad_offset_and_size() {
+        for variant in VARIANTS {
+            // a match before the first byte
+            let mut writer = BitWriter::new(8);
+            writer.literal(b'a');
+            writer.copy(variant, 2, 2);
+            let src = writer.end(variant);
+            assert!(matches!(nrv(&src, variant, 8, 3), Err(Error::Invalid)));
+            // more output than the header declares
+            let src = stream(variant, 8, 40);
+            assert!(nrv(&src, variant, 8, 10).is_err());
+        }
+    }
+
+    #[test]
+    fn nrv_methods() {
+        let src = stream(Nrv::E, 32, 40);
+        assert_eq!(decompress(&src, M_NRV2E_LE32, 44).ok(), Some(expected(40)));
+        assert!(matches!(
+            decompress(&src, M_NRV2E_LE32, 45),
+            Err(Error::Invalid)
+        ));
+        assert!(matches!(
+            decompress(&src, 0xff, 44),
+            Err(Error::UnsupportedCompression)
+        ));
+    }
+
+    #[test]
+    fn lzma_truncated() {
+        assert!(matches!(lzma(&[], 10), Err(Error::Bounds)));
+        // lc + lp does not match the first byte
+        assert!(matches!(lzma(&[0x1a, 0x03, 0], 10), Err(Error::Invalid)));
+        assert!(lzma(&[0x18, 0x03, 0, 0, 0], 10).is_err());
+    }
+}
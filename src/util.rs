@@ -0,0 +1,98 @@
+pub fn get_pe_file_size(pe: &[u8]) -> usize {
+    pe.len()
+    // pe.get_buffer().as_ref().len()
+}
+
+pub fn round_to_pe_sz_with_offset(pe: &[u8], offset: usize, value: usize) -> usize {
+    let pe_sz = get_pe_file_size(pe);
+    // a size read from the file can be anything, even enough to overflow
+    match offset.saturating_add(value) > pe_sz {
+        true => pe_sz.saturating_sub(offset),
+        false => value,
+    }
+}
+pub fn safe_read(pe: &[u8], offset: usize, size: usize) -> &[u8] {
+    let pe_sz = get_pe_file_size(pe);
+    if offset >= pe_sz {
+        return &pe[0..0];
+    }
+
+    let safe_size = round_to_pe_sz_with_offset(pe, offset, size);
+    &pe[offset..offset + safe_size]
+}
+
+/// Reads an unsigned integer of `size` bytes, at most 8, at `offset`.
+pub fn read_uint(data: &[u8], offset: usize, size: usize, le: bool) -> Option<u64> {
+    let bytes = data.get(offset..offset.checked_add(size)?)?;
+    let mut buf = [0u8; 8];
+    if le {
+        buf.get_mut(..size)?.copy_from_slice(bytes);
+        Some(u64::from_le_bytes(buf))
+    } else {
+        buf.get_mut(8usize.checked_sub(size)?..)?
+            .copy_from_slice(bytes);
+        Some(u64::from_be_bytes(buf))
+    }
+}
+
+pub fn read_u32(data: &[u8], offset: usize, le: bool) -> Option<u32> {
+    read_uint(data, offset, 4, le).map(|value| value as u32)
+}
+
+pub fn le_u16(data: &[u8], offset: usize) -> Option<u16> {
+    read_uint(data, offset, 2, true).map(|value| value as u16)
+}
+
+pub fn le_u32(data: &[u8], offset: usize) -> Option<u32> {
+    read_u32(data, offset, true)
+}
+
+pub fn le_u64(data: &[u8], offset: usize) -> Option<u64> {
+    read_uint(data, offset, 8, true)
+}
+
+pub fn be_u32(data: &[u8], offset: usize) -> Option<u32> {
+    read_u32(data, offset, false)
+}
+
+/// Offset of the first occurrence of `needle` in `haystack`.
+pub fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack
+        .windows(needle.len())
+        .position(|window| window == needle)
+}
+
+/// Offset of the last occurrence of `needle` in `haystack`.
+pub fn rfind(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack
+        .windows(needle.len())
+        .rposition(|window| window == needle)
+}
+
+/// Offset of the first occurrence of `needle` in `haystack`, ignoring ASCII case.
+pub fn find_ignore_case(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack
+        .windows(needle.len())
+        .position(|window| window.eq_ignore_ascii_case(needle))
+}
+
+/// Offsets of every occurrence of `needle` in `haystack`.
+pub fn find_all<'a>(haystack: &'a [u8], needle: &'a [u8]) -> impl Iterator<Item = usize> + 'a {
+    haystack
+        .windows(needle.len())
+        .enumerate()
+        .filter(move |(_, window)| *window == needle)
+        .map(|(position, _)| position)
+}
+
+/// String up to the first NUL, the whole of `bytes` when there is none.
+pub fn c_string(bytes: &[u8]) -> String {
+    let end = bytes.iter().position(|&b| b == 0).unwrap_or(bytes.len());
+    String::from_utf8_lossy(&bytes[..end]).to_string()
+}
+
+/// NUL terminated string starting at `offset`, without the NUL.
+pub fn nul_terminated(data: &[u8], offset: usize) -> Option<&[u8]> {
+    let bytes = data.get(offset..)?;
+    Some(&bytes[..bytes.iter().position(|&b| b == 0)?])
+}